@@ -0,0 +1,189 @@
+use super::engine::{value_of, HasLookup};
+use super::error::QueryError;
+use super::sql::{Expr, AGGREGATES};
+use super::value::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// The rows sharing one set of `group by` values. Plain columns read from the
+/// first row, aggregate functions run over all of them.
+pub struct Group<'a, T> {
+    pub rows: Vec<&'a T>,
+}
+
+impl<T: HasLookup> HasLookup for Group<'_, T> {
+    fn lookup(&self, attribute: &str) -> Value {
+        match self.rows.first() {
+            Some(r) => r.lookup(attribute),
+            None => Value::Null,
+        }
+    }
+
+    fn aggregate(&self, func: &Expr) -> Value {
+        aggregate(func, &self.rows)
+    }
+}
+
+/// Splits rows into groups by the values of the `group by` expressions,
+/// keeping the order each group was first seen in. Without a `group by`
+/// everything lands in a single group, even if there are no rows.
+pub fn group_rows<'a, T: HasLookup>(rows: &'a [T], group_by: &[Expr]) -> Vec<Group<'a, T>> {
+    if group_by.is_empty() {
+        return vec![Group {
+            rows: rows.iter().collect(),
+        }];
+    }
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut groups: Vec<Group<T>> = Vec::new();
+    for row in rows {
+        let key: Vec<Value> = group_by.iter().map(|x| value_of(x, row)).collect();
+        let i = *index.entry(format!("{:?}", key)).or_insert_with(|| {
+            groups.push(Group { rows: Vec::new() });
+            groups.len() - 1
+        });
+        groups[i].rows.push(row);
+    }
+    groups
+}
+
+/// Checks the functions used in a query exist and are called with the right
+/// arguments. Aggregates aren't allowed in `where` or inside each other.
+pub fn check_functions(expr: &Expr, allow_aggregate: bool) -> Result<(), QueryError> {
+    match expr {
+        Expr::Function {
+            name,
+            distinct,
+            args,
+        } => {
+            if !AGGREGATES.contains(&name.as_str()) {
+                return Err(QueryError::invalid(
+                    format!("Unknown function {}", name),
+                    name,
+                ));
+            }
+            if !allow_aggregate {
+                return Err(QueryError::invalid(
+                    format!("Aggregate function {} is not allowed here", name),
+                    name,
+                ));
+            }
+            let arity_ok = match name.as_str() {
+                "count" => args.len() <= 1 && !(args.is_empty() && *distinct),
+                "group_concat" => args.len() == 1 || args.len() == 2,
+                _ => args.len() == 1,
+            };
+            if !arity_ok {
+                return Err(QueryError::invalid(
+                    format!("Wrong number of arguments to {}", expr),
+                    name,
+                ));
+            }
+            args.iter().try_for_each(|x| check_functions(x, false))
+        }
+        Expr::BinaryOp { left, right, .. } => {
+            check_functions(left, allow_aggregate)?;
+            check_functions(right, allow_aggregate)
+        }
+        Expr::Not(e) | Expr::IsNull { expr: e, .. } => check_functions(e, allow_aggregate),
+        Expr::InList { expr, list, .. } => {
+            check_functions(expr, allow_aggregate)?;
+            list.iter()
+                .try_for_each(|x| check_functions(x, allow_aggregate))
+        }
+        Expr::Column(_) | Expr::Literal(_) => Ok(()),
+    }
+}
+
+fn distinct(values: Vec<Value>) -> Vec<Value> {
+    let mut out: Vec<Value> = Vec::new();
+    for v in values {
+        if !out.iter().any(|x| x.compare(&v) == Some(Ordering::Equal)) {
+            out.push(v);
+        }
+    }
+    out
+}
+
+/// The values that are numbers, or text that reads as one.
+fn numbers(values: &[Value]) -> Vec<f64> {
+    values.iter().filter_map(|x| x.as_f64()).collect()
+}
+
+fn sum(values: &[Value]) -> Value {
+    if values.is_empty() {
+        return Value::Null;
+    }
+    if values.iter().all(|x| matches!(x, Value::Integer(_))) {
+        let mut total: Option<i64> = Some(0);
+        for v in values {
+            if let Value::Integer(i) = v {
+                total = total.and_then(|t| t.checked_add(*i));
+            }
+        }
+        // Too big for an integer, so fall back to a real like sqlite's total().
+        if let Some(total) = total {
+            return Value::Integer(total);
+        }
+    }
+    Value::Real(numbers(values).iter().fold(0.0, |a, b| a + b))
+}
+
+fn avg(values: &[Value]) -> Value {
+    let numbers = numbers(values);
+    if numbers.is_empty() {
+        return Value::Null;
+    }
+    Value::Real(numbers.iter().fold(0.0, |a, b| a + b) / numbers.len() as f64)
+}
+
+fn aggregate<T: HasLookup>(func: &Expr, rows: &[&T]) -> Value {
+    let (name, is_distinct, args) = match func {
+        Expr::Function {
+            name,
+            distinct,
+            args,
+        } => (name.as_str(), *distinct, args),
+        _ => return Value::Null,
+    };
+    if args.is_empty() {
+        return Value::Integer(rows.len() as i64);
+    }
+    let mut values: Vec<Value> = rows
+        .iter()
+        .map(|r| value_of(&args[0], *r))
+        .filter(|x| !x.is_null())
+        .collect();
+    if is_distinct {
+        values = distinct(values);
+    }
+    match name {
+        "count" => Value::Integer(values.len() as i64),
+        "sum" => sum(&values),
+        "avg" => avg(&values),
+        "min" => values
+            .into_iter()
+            .min_by(|a, b| a.sort_cmp(b))
+            .unwrap_or(Value::Null),
+        "max" => values
+            .into_iter()
+            .max_by(|a, b| a.sort_cmp(b))
+            .unwrap_or(Value::Null),
+        "group_concat" => {
+            if values.is_empty() {
+                return Value::Null;
+            }
+            let sep = match (args.get(1), rows.first()) {
+                (Some(e), Some(r)) => value_of(e, *r).to_string(),
+                _ => ",".to_string(),
+            };
+            Value::Text(
+                values
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>()
+                    .join(&sep),
+            )
+        }
+        _ => Value::Null,
+    }
+}
@@ -1,124 +1,253 @@
-use super::engine;
-use super::interface;
+use super::cli::{self, Options};
+use super::commands;
+use super::editor;
+use super::interface::{self, Layout};
+use super::output::Format;
 use regex::Regex;
-use std::io;
+use rusty_osquery::sql::{is_complete, split_statements};
+use rusty_osquery::Engine;
+use rustyline::error::ReadlineError;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::time::Instant;
 
-pub struct UserInput {
-    table_name: String,
-    params: Vec<String>,
-    filter_string: String,
+/// What the shell keeps between statements.
+pub struct Shell {
+    pub engine: Engine,
+    pub format: Format,
+    pub headers: bool,
+    pub timer: bool,
+    pub layout: Layout,
+    /// Where results go, and the file name if it isn't stdout.
+    pub output: Box<dyn Write>,
+    pub output_name: Option<String>,
+}
+
+impl Shell {
+    /// True if results go to the terminal, rather than a file or a pipe.
+    pub fn terminal(&self) -> bool {
+        self.output_name.is_none() && io::stdout().is_terminal()
+    }
 }
 
 pub fn parse_schema_query(input: String) -> Result<String, String> {
     let re = Regex::new(r"show\s([a-zA-z_]+)\.schema;").unwrap();
-    let mut capts = re.captures_iter(&input[..]);
+    let mut capts = re.captures_iter(&input);
     let m = capts.next();
     match m {
-        Some(p) => {
-            Ok(p[1].to_string())
-        },
-        _ => Err("Error! If you can see this message something is really wrong. My bad.".to_string()),
+        Some(p) => Ok(p[1].to_string()),
+        _ => {
+            Err("Error! If you can see this message something is really wrong. My bad.".to_string())
+        }
     }
 }
 
-pub fn parse_option(input: String) -> Result<UserInput, String> {
-    let re = Regex::new(r"select\s([a-zA-z_\*,|\s?]+)\sfrom\s([a-z_]+)(\swhere\s(.*))?;").unwrap();
-    let mut capts = re.captures_iter(&input[..]);
-    let m = capts.next();
-
-    match m {
-        Some(p) => {
-            //println!("{:?}", p);
-            let par: Vec<String> = p[1].split(",").map(|x| x.trim().to_string()).collect();
-            let filter_string = match p.get(4) {
-                Some(_) => p[4].to_string(),
-                _ => String::new(),
-            };
-
-            let ui = UserInput {
-                table_name: p[2].to_string(),
-                params: par,
-                filter_string: filter_string,
-            };
-            Ok(ui)
+/// Runs a query and prints the result, returning false if it failed.
+fn handle_input(shell: &mut Shell, input: &str) -> bool {
+    let start = Instant::now();
+    let res = match shell.engine.query(input) {
+        Ok(res) => res,
+        Err(e) => {
+            interface::print_error(&e, input);
+            return false;
         }
-        _ => Err("Oh Nos! Expected query syntax is select <cols> from <table> where <cond>;\n".to_string()),
+    };
+    let elapsed = start.elapsed();
+    let terminal = shell.terminal();
+    if let Err(e) = interface::print_result(
+        &mut shell.output,
+        &res,
+        shell.format,
+        shell.headers,
+        &shell.layout,
+        terminal,
+    ) {
+        eprintln!("⚠ Oh Nos! Couldn't write the result: {}", e);
+        return false;
+    }
+    interface::print_warnings(&res);
+    if shell.timer {
+        eprintln!(
+            "Run time: {:.3}s, {} row(s)",
+            elapsed.as_secs_f64(),
+            res.rows.len()
+        );
     }
+    true
 }
 
-fn handle_input(ui: &mut UserInput) {
-    match ui.table_name.as_str() {
-        "procs" => {
-            /*let filter_items_test = engine::FilterItems{
-                filters: vec![engine::FilterItem::new("uid".to_string(), engine::FilterOp::Eq, "1000".to_string())]
-            };*/
-            match engine::query_procs(&mut ui.params, &ui.filter_string) {
-                Ok(res) => interface::print_data_table(res),
-                _ => println!("ERROR!"),
+pub fn get_schema(shell: &mut Shell, table: String) -> bool {
+    let terminal = shell.terminal();
+    match shell.engine.table(&table) {
+        Some(t) => match interface::print_schema(&mut shell.output, t, terminal) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("⚠ Oh Nos! Couldn't write the schema: {}", e);
+                false
             }
         },
-        "proc_maps" => match engine::query_proc_maps(&mut ui.params, &ui.filter_string) {
-            Ok(res) => interface::print_data_table(res),
-            _ => println!("ERROR!")
-        },
-        "net" => match engine::query_net(&mut ui.params, &ui.filter_string) {
-            Ok(res) => interface::print_data_table(res),
-            _ => println!("ERROR!")
-        },
-        "fs" => match engine::query_dir(&mut ui.params, &ui.filter_string) {
-            Ok(res) => interface::print_data_table(res),
-            _ => println!("ERROR!"),
-        },
-        "os_version" => match engine::do_get_os_version_info(&mut ui.params) {
-            Ok(res) => interface::print_hash_table(res),
-            _ => println!("ERROR!"),
-        },
-        _ => println!("Uh Oh! Table {} does not exist.", ui.table_name),
+        None => {
+            eprintln!("Uh Oh! Table {} does not exist!", table);
+            false
+        }
     }
 }
 
-pub fn get_schema(table: String) {
-    match table.as_str() {
-        "os_version" => interface::print_os_version_schema(),
-        "procs" => interface::print_procs_schema(),
-        "fs" => interface::print_fs_schema(),
-        "net" => interface::print_net_schema(),
-        "proc_maps" => interface::print_proc_map_schema(),
-        _ => println!("Uh Oh! Table {} does not exist!", table),
+/// Runs one statement, which may be a query or one of the `show` commands.
+fn execute(shell: &mut Shell, input: &str) -> bool {
+    if input == "show dog;" {
+        interface::dog();
+        return true;
+    }
+    match parse_schema_query(input.to_string()) {
+        Ok(x) => get_schema(shell, x),
+        _ => handle_input(shell, input),
     }
 }
 
-pub fn mainloop() {
-    interface::print_banner();
+/// Runs every statement and dot command in a script, carrying on past
+/// failures. A dot command takes up a line of its own, and statements run
+/// once they end with `;`. Returns the exit code.
+pub fn run_script(shell: &mut Shell, script: &str) -> i32 {
+    let mut ok = true;
+    let mut pending = String::new();
+    for line in script.lines() {
+        if pending.trim().is_empty() && line.trim_start().starts_with('.') {
+            ok &= commands::run(shell, line.trim());
+            continue;
+        }
+        pending.push_str(line);
+        pending.push('\n');
+        if is_complete(&pending) {
+            ok &= execute_all(shell, &pending);
+            pending.clear();
+        }
+    }
+    ok &= execute_all(shell, &pending);
+    i32::from(!ok)
+}
+
+fn execute_all(shell: &mut Shell, sql: &str) -> bool {
+    let mut ok = true;
+    for stmt in split_statements(sql) {
+        ok &= execute(shell, stmt);
+    }
+    ok
+}
+
+pub fn read_script(path: &str) -> io::Result<String> {
+    let mut script = String::new();
+    if path == "-" {
+        io::stdin().read_to_string(&mut script)?;
+    } else {
+        script = fs::read_to_string(path)?;
+    }
+    Ok(script)
+}
+
+/// Runs the shell with the command line arguments, returning the exit code.
+pub fn run() -> i32 {
+    let opts: Options = match cli::parse_args(env::args().skip(1)) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            return 2;
+        }
+    };
+    if opts.help {
+        println!("{}", cli::USAGE);
+        return 0;
+    }
+
+    let mut engine = Engine::new();
+    if let Some(root) = &opts.root {
+        engine = engine.with_root(root);
+    }
+    if let Some(proc_root) = &opts.proc_root {
+        engine = engine.with_proc_root(proc_root);
+    }
+    let mut shell = Shell {
+        engine,
+        format: opts.format,
+        headers: true,
+        timer: false,
+        layout: Layout::default(),
+        output: Box::new(io::stdout()),
+        output_name: None,
+    };
+
+    if let Some(query) = &opts.query {
+        return run_script(&mut shell, query);
+    }
+    let script = match &opts.script {
+        Some(path) => Some(path.as_str()),
+        None if !io::stdin().is_terminal() => Some("-"),
+        None => None,
+    };
+    if let Some(path) = script {
+        return match read_script(path) {
+            Ok(s) => run_script(&mut shell, &s),
+            Err(e) => {
+                eprintln!("Couldn't read {}: {}", path, e);
+                2
+            }
+        };
+    }
+
+    mainloop(&mut shell, !opts.no_banner);
+    0
+}
+
+fn mainloop(shell: &mut Shell, banner: bool) {
+    if banner {
+        interface::print_banner();
+    }
+    let mut editor = match editor::new(shell.engine.tables()) {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("⚠ Oh Nos! Couldn't start the line editor: {}", e);
+            return;
+        }
+    };
+    let history = editor::history_path();
+    if let Some(path) = &history {
+        // There's no history the first time round.
+        let _ = editor.load_history(path);
+    }
     loop {
         interface::print_prompt();
-        let input = get_option();
-        if input == "q" || input == "quit" {
-            break;
-        } if input == "h" || input == "help" {
-            interface::print_help();
-        } else if input == "show dog;" {
-            interface::dog();
-        } else {
-            match parse_schema_query(input.clone()) {
-            Ok(x) => {
-                get_schema(x)
-            },
-            _ => {
-             match parse_option(input) {
-                Ok(mut ui) => handle_input(&mut ui),
-                Err(e) => println!("⚠ {}", e),
-                }  
+        let input = match editor.readline(">>> ") {
+            Ok(x) => x,
+            // Ctrl-C throws away what's been typed, Ctrl-D quits.
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("⚠ Oh Nos! {}", e);
+                break;
             }
+        };
+        let input = input.trim();
+        if input.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(input);
+        if let Some(path) = &history {
+            if let Err(e) = editor.save_history(path) {
+                eprintln!(
+                    "⚠ Oh Nos! Couldn't save history to {}: {}",
+                    path.display(),
+                    e
+                );
             }
         }
-    }
-}
 
-pub fn get_option() -> String {
-    let mut input = String::new();
-    io::stdin()
-        .read_line(&mut input)
-        .expect("can't process input");
-    input.trim().to_string()
+        if input == "q" || input == "quit" || input == ".quit" {
+            break;
+        } else if input == "h" || input == "help" {
+            interface::print_help(&shell.engine);
+        } else {
+            run_script(shell, input);
+        }
+    }
 }
@@ -0,0 +1,52 @@
+use super::output::Format;
+
+pub const USAGE: &str = "Usage: rusty_osquery [options] [script]
+
+Runs the interactive shell, or the statements in `script` (- for stdin)
+or --query and exits. Statements piped into stdin are run too.
+
+Options:
+  -e, --query <sql>       Run <sql> and exit
+  -f, --format <format>   Output format: table, markdown, json, ndjson, csv,
+                          tsv or line (default: table)
+      --no-banner         Don't print the banner when the shell starts
+      --root <dir>        Read files and procfs from under <dir> instead of /
+      --proc-root <dir>   Read procfs from <dir> instead of <root>/proc
+  -h, --help              Print this help
+
+Exits with 1 if any statement fails, and 2 if the options are wrong.";
+
+/// What the shell was asked to do on the command line.
+#[derive(Debug, Default)]
+pub struct Options {
+    pub query: Option<String>,
+    pub script: Option<String>,
+    pub no_banner: bool,
+    pub format: Format,
+    pub root: Option<String>,
+    pub proc_root: Option<String>,
+    pub help: bool,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut opts = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "-e" | "--query" => opts.query = Some(value(&arg)?),
+            "-f" | "--format" => opts.format = value(&arg)?.parse()?,
+            "--no-banner" => opts.no_banner = true,
+            "--root" => opts.root = Some(value(&arg)?),
+            "--proc-root" => opts.proc_root = Some(value(&arg)?),
+            "-h" | "--help" => opts.help = true,
+            x if x.starts_with('-') && x != "-" => return Err(format!("Unknown option {}", x)),
+            _ if opts.script.is_some() => return Err(format!("Unexpected argument {}", arg)),
+            _ => opts.script = Some(arg),
+        }
+    }
+    if opts.query.is_some() && opts.script.is_some() {
+        return Err("Give either --query or a script, not both".to_string());
+    }
+    Ok(opts)
+}
@@ -0,0 +1,198 @@
+use super::app::{get_schema, read_script, run_script, Shell};
+use super::output::Format;
+use super::watch;
+use std::fs::File;
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
+
+const HELP: &str = "\
+.tables [pattern]   List the tables, or the ones with pattern in their name
+.schema [table]     Show the columns of a table, or of every table
+.mode [format]      Print results as one of: {formats}
+.headers on|off     Print column names above results or not
+.output [file]      Send results to file, or back to the terminal
+.read <file>        Run the statements in file
+.timer on|off       Print how long each query takes
+.width [column] <n> Cut values wider than n short, in one column or all of them
+.wrap on|off        Wrap values that are too wide instead of cutting them short
+.pager on|off       Page tables that are taller than the terminal
+.watch <secs> <sql> Rerun a query every few seconds and show what changed
+.show               Show the current settings
+.quit               Leave the shell";
+
+fn on_off(arg: &str) -> Result<bool, String> {
+    match arg {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("Expected on or off, not '{}'", arg)),
+    }
+}
+
+/// Lists the tables to wherever `.output` points, like `.schema` does.
+fn tables(shell: &mut Shell, pattern: &str) -> Result<(), String> {
+    for t in shell.engine.tables() {
+        if t.name().contains(pattern) {
+            writeln!(shell.output, "{}", t.name())
+                .map_err(|e| format!("Couldn't write the tables: {}", e))?;
+        }
+    }
+    shell
+        .output
+        .flush()
+        .map_err(|e| format!("Couldn't write the tables: {}", e))
+}
+
+fn schema(shell: &mut Shell, table: &str) -> Result<(), String> {
+    let names: Vec<&str> = match table {
+        "" => shell.engine.tables().iter().map(|t| t.name()).collect(),
+        t => vec![t],
+    };
+    for name in names {
+        if !get_schema(shell, name.to_string()) {
+            return Err(format!("Couldn't show the schema of {}", name));
+        }
+    }
+    Ok(())
+}
+
+fn mode(shell: &mut Shell, arg: &str) -> Result<(), String> {
+    if arg.is_empty() {
+        println!("{} (one of {})", shell.format, Format::names());
+    } else {
+        shell.format = arg.parse()?;
+    }
+    Ok(())
+}
+
+fn output(shell: &mut Shell, path: &str) -> Result<(), String> {
+    if path.is_empty() || path == "stdout" {
+        shell.output = Box::new(io::stdout());
+        shell.output_name = None;
+        return Ok(());
+    }
+    let file = File::create(path).map_err(|e| format!("Couldn't open {}: {}", path, e))?;
+    shell.output = Box::new(file);
+    shell.output_name = Some(path.to_string());
+    Ok(())
+}
+
+fn read(shell: &mut Shell, path: &str) -> Result<(), String> {
+    if path.is_empty() {
+        return Err("Usage: .read <file>".to_string());
+    }
+    let script = read_script(path).map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+    match run_script(shell, &script) {
+        0 => Ok(()),
+        _ => Err(format!("Some statements in {} failed", path)),
+    }
+}
+
+/// `.width 40` limits every column, `.width cmdline 80` just one, and 0
+/// means no limit.
+fn width(shell: &mut Shell, arg: &str) -> Result<(), String> {
+    let usage = || "Usage: .width [column] <n>".to_string();
+    let (column, n) = match arg.split_whitespace().collect::<Vec<_>>()[..] {
+        [n] => (None, n),
+        [c, n] => (Some(c), n),
+        _ => return Err(usage()),
+    };
+    let n: usize = n.parse().map_err(|_| usage())?;
+    let layout = &mut shell.layout;
+    match column {
+        Some(c) => {
+            layout.widths.retain(|x| x.0 != c);
+            layout.widths.push((c.to_string(), n));
+        }
+        None => {
+            layout.max_width = n;
+            layout.widths.clear();
+        }
+    }
+    Ok(())
+}
+
+/// `.watch 2 select pid, name from procs;` reruns the query every two
+/// seconds until q is pressed.
+fn watch(shell: &Shell, arg: &str) -> Result<(), String> {
+    let usage = || "Usage: .watch <seconds> <query>".to_string();
+    let (secs, sql) = arg.split_once(char::is_whitespace).ok_or_else(usage)?;
+    let secs: f64 = secs.trim_end_matches('s').parse().map_err(|_| usage())?;
+    if !(secs > 0.0 && secs.is_finite()) {
+        return Err("The interval has to be more than 0 seconds".to_string());
+    }
+    if !io::stdout().is_terminal() {
+        return Err(".watch needs a terminal".to_string());
+    }
+    watch::watch(
+        &shell.engine,
+        sql.trim(),
+        Duration::from_secs_f64(secs),
+        &shell.layout,
+    )
+}
+
+fn show(shell: &Shell) -> Result<(), String> {
+    let on_off = |x: bool| if x { "on" } else { "off" };
+    let roots = shell.engine.roots();
+    println!("     mode: {}", shell.format);
+    println!("  headers: {}", on_off(shell.headers));
+    println!(
+        "   output: {}",
+        shell.output_name.as_deref().unwrap_or("stdout")
+    );
+    println!("    timer: {}", on_off(shell.timer));
+    let mut widths = vec![match shell.layout.max_width {
+        0 => "no limit".to_string(),
+        n => n.to_string(),
+    }];
+    widths.extend(
+        shell
+            .layout
+            .widths
+            .iter()
+            .map(|(c, n)| format!("{} {}", c, n)),
+    );
+    println!("    width: {}", widths.join(", "));
+    println!("     wrap: {}", on_off(shell.layout.wrap));
+    println!("    pager: {}", on_off(shell.layout.pager));
+    println!("     root: {}", roots.root().display());
+    println!("proc root: {}", roots.proc_root().display());
+    Ok(())
+}
+
+/// Runs a dot command like `.mode csv`. Returns false if it failed.
+pub fn run(shell: &mut Shell, line: &str) -> bool {
+    let (name, arg) = match line.split_once(char::is_whitespace) {
+        Some((n, a)) => (n, a.trim()),
+        None => (line, ""),
+    };
+    let res = match name {
+        ".tables" => tables(shell, arg),
+        ".schema" => schema(shell, arg),
+        ".mode" => mode(shell, arg),
+        ".headers" => on_off(arg).map(|x| shell.headers = x),
+        ".output" => output(shell, arg),
+        ".read" => read(shell, arg),
+        ".timer" => on_off(arg).map(|x| shell.timer = x),
+        ".width" => width(shell, arg),
+        ".wrap" => on_off(arg).map(|x| shell.layout.wrap = x),
+        ".pager" => on_off(arg).map(|x| shell.layout.pager = x),
+        ".watch" => watch(shell, arg),
+        ".show" => show(shell),
+        ".help" => {
+            println!("{}", HELP.replace("{formats}", &Format::names()));
+            Ok(())
+        }
+        // Only means something at the prompt, there's nothing to leave in a
+        // script.
+        ".quit" => Ok(()),
+        _ => Err(format!("Unknown command {}, try .help", name)),
+    };
+    match res {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("⚠ Oh Nos! {}", e);
+            false
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use regex::Regex;
+use rusty_osquery::sql::{is_complete, KEYWORDS};
+use rusty_osquery::tables::Table;
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Config, Context, Editor, Helper};
+use std::env;
+use std::path::PathBuf;
+
+const HISTORY_FILE: &str = ".rusty_osquery_history";
+
+/// True for the shell's own commands, which don't need a `;`.
+pub fn is_command(input: &str) -> bool {
+    let input = input.trim();
+    matches!(input, "" | "q" | "quit" | "h" | "help") || input.starts_with('.')
+}
+
+/// Where history is kept between runs, in the home directory.
+pub fn history_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|h| PathBuf::from(h).join(HISTORY_FILE))
+}
+
+/// Completes and validates input as it's typed.
+pub struct SqlHelper {
+    tables: &'static [&'static dyn Table],
+}
+
+impl SqlHelper {
+    /// The tables after `from` and `join` in `line`, with their aliases.
+    fn tables_in(&self, line: &str) -> Vec<(&'static dyn Table, Option<String>)> {
+        let re = Regex::new(r"(?i)\b(?:from|join)\s+(\w+)(?:\s+(?:as\s+)?(\w+))?").unwrap();
+        re.captures_iter(line)
+            .filter_map(|c| {
+                let table = self.tables.iter().find(|t| t.name() == &c[1])?;
+                let alias = c
+                    .get(2)
+                    .map(|a| a.as_str().to_lowercase())
+                    .filter(|a| !KEYWORDS.contains(&a.as_str()));
+                Some((*table, alias))
+            })
+            .collect()
+    }
+
+    fn candidates(&self, line: &str, before: &str, word: &str) -> Vec<String> {
+        let tables = self.tables_in(line);
+        if let Some((prefix, _)) = word.split_once('.') {
+            return tables
+                .iter()
+                .filter(|(t, alias)| alias.as_deref().unwrap_or(t.name()) == prefix)
+                .flat_map(|(t, _)| t.columns().iter())
+                .map(|c| format!("{}.{}", prefix, c.name))
+                .collect();
+        }
+        let prev = before.split_whitespace().last().map(|x| x.to_lowercase());
+        if matches!(prev.as_deref(), Some("from" | "join")) {
+            return self.tables.iter().map(|t| t.name().to_string()).collect();
+        }
+        let mut out: Vec<String> = tables
+            .iter()
+            .flat_map(|(t, _)| t.columns().iter())
+            .map(|c| c.name.to_string())
+            .collect();
+        out.extend(KEYWORDS.iter().map(|k| k.to_string()));
+        out.extend(self.tables.iter().map(|t| t.name().to_string()));
+        out
+    }
+}
+
+impl Completer for SqlHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !(c.is_alphanumeric() || *c == '_' || *c == '.'))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &before[start..];
+        let lower = word.to_lowercase();
+        let mut found: Vec<String> = self
+            .candidates(line, &before[..start], word)
+            .into_iter()
+            .filter(|c| c.starts_with(&lower) || c.starts_with(word))
+            .collect();
+        found.sort();
+        found.dedup();
+        let pairs = found
+            .into_iter()
+            .map(|c| Pair {
+                display: c.clone(),
+                replacement: c,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for SqlHelper {
+    type Hint = String;
+}
+
+impl Highlighter for SqlHelper {}
+
+impl Validator for SqlHelper {
+    /// Keeps reading lines until the statement ends with a `;`.
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+        if is_command(input) || is_complete(input) {
+            Ok(ValidationResult::Valid(None))
+        } else {
+            Ok(ValidationResult::Incomplete)
+        }
+    }
+}
+
+impl Helper for SqlHelper {}
+
+pub fn new(
+    tables: &'static [&'static dyn Table],
+) -> rustyline::Result<Editor<SqlHelper, DefaultHistory>> {
+    let config = Config::builder()
+        .max_history_size(1000)?
+        .history_ignore_dups(true)?
+        .auto_add_history(false)
+        .build();
+    let mut editor = Editor::with_config(config)?;
+    editor.set_helper(Some(SqlHelper { tables }));
+    Ok(editor)
+}
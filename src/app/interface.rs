@@ -1,10 +1,12 @@
+use super::output::{self, Format};
+use super::pager;
 use ansi_term::{Colour, Style};
-use std::collections::HashMap;
-use std::io::{self, Write};
-use termimad;
+use rusty_osquery::tables::Table;
+use rusty_osquery::{Engine, QueryError, ResultSet};
+use std::io::{self, IsTerminal, Write};
 use termimad::Alignment;
-use termimad::*;
 use termimad::MadSkin;
+use termimad::*;
 
 pub fn print_banner() {
     let banner = "                              
@@ -29,16 +31,13 @@ pub fn print_banner() {
     
     ";
     println!("{}", Colour::Cyan.paint(banner));
-
 }
 
 pub fn print_prompt() {
     println!("h - help, q - quit");
-    print!(">>> ");
-    io::stdout().flush().unwrap();
 }
 
-pub fn print_help() {
+pub fn print_help(engine: &Engine) {
     let mut skin = MadSkin::default();
     skin.set_headers_fg(rgb(31, 255, 15));
     let text_template = r#"
@@ -49,19 +48,23 @@ pub fn print_help() {
 |:-|:-|
 |**Table**|**Description**|
 |:-|:-|
-| procs | Processes running on the system |
-| proc_maps | Data mapped into process memory |
-| net | Network connections |
-| fs | Query the file system |
-| os_version | Query the operating system version |
-|-
+{tables}|-
 
 ### Command Syntax:
 
-select <columns> from <table> where <key value pairs>;
+select <columns> from <table> where <conditions> group by <columns> having <conditions> order by <columns> limit <n> offset <m>;
 
-* values must be surronded by quotations
-* key-value pairs must be seperated by a comma
+* conditions are written as <column> <op> <value>, op is one of = != < <= > >= like
+* string values must be surronded by quotations, numbers, true, false and null are written bare
+* use <column> is null or <column> is not null to check for missing values
+* use <column> in (<value>, <value>) to match any of a list of values
+* proc_maps needs a pid and fs needs a path in the where clause, e.g. where pid in (1, 2) or where path = "/tmp"
+* conditions can be combined with and, or, not and parentheses, a comma works like and
+* where, group by, having, order by, limit and offset are optional
+* columns can be given a name with as, e.g. select uid, count(*) as n
+* aggregate functions: count(*), count(<col>), count(distinct <col>), sum, min, max, avg, group_concat(<col>, <sep>)
+* order by columns can be followed by asc or desc
+* tables can be joined with join or left join and an on condition, give them a short name to qualify columns, e.g. select p.pid, m.file_name from procs p join proc_maps m on m.pid = p.pid
 
 ### Table Schema:
 
@@ -69,109 +72,365 @@ To print table schema:
 
 show <table name>.schema;
 
-"#;
-    println!("");
-    println!("{}", skin.term_text(&text_template[..]));
-    println!("\n");
-}
+### Editing:
 
-pub fn print_procs_schema() {
-    println!("");
+* a statement can span several lines, it runs once it ends with ;
+* tab completes keywords, table names and the columns of the tables in the from clause
+* up and down go through history, ctrl-r searches it, ctrl-c clears the input and ctrl-d quits
 
-    let mut skin = MadSkin::default();
-    let mut text_template: String  = "|:-|:-|\n|**pid**|The Process ID|\n|**uid**|The ID of the user who ran the program|\n|**cmdline**|The command used to run the program|\n|-".to_string();
+### Shell Commands:
 
-    skin.paragraph.align = Alignment::Left;
-    skin.table.align = Alignment::Left;
+* .tables [pattern] lists the tables, .schema [table] shows their columns
+* .mode <table, markdown, json, ndjson, csv, tsv or line> changes how results are printed
+* .headers on|off turns column names on or off
+* .output <file> sends results to a file, .output on its own sends them back to the terminal
+* .read <file> runs the statements in a file
+* .timer on|off prints how long each query takes
+* .width [column] <n> cuts values wider than n short, .wrap on wraps them instead
+* .pager on|off pages tables taller than the terminal, q leaves the pager and / searches it
+* .watch <secs> <sql> reruns a query every few seconds, marking rows that were added, changed or removed
+* .show shows the current settings, .help lists these commands
 
-    println!("{}", skin.term_text(&text_template[..]));
+"#;
+    let table_list: String = engine
+        .tables()
+        .iter()
+        .map(|t| format!("| {} | {} |\n", t.name(), t.description()))
+        .collect();
+    let text = text_template.replace("{tables}", &table_list);
+    println!();
+    println!("{}", skin.term_text(&text));
     println!("\n");
 }
 
-pub fn print_fs_schema() {
-    println!("");
+/// Prints the columns of a table, styled only if `terminal` says `out` is
+/// the terminal.
+pub fn print_schema(out: &mut dyn Write, table: &dyn Table, terminal: bool) -> io::Result<()> {
+    writeln!(out)?;
 
-    let mut skin = MadSkin::default();
-    let mut text_template: String  = "|:-|:-|\n|**name*|The name of the file|\n|**dir**|The file type|\n|**uid**|The uid of the file owner|\n|**created**|The file creation time|\n|-".to_string();
+    let mut skin = if terminal {
+        MadSkin::default()
+    } else {
+        MadSkin::no_style()
+    };
+    let mut text_template: String =
+        "|:-|:-|:-|:-|\n|**Column**|**Type**|**Description**|**Notes**|\n|:-|:-|:-|:-|\n"
+            .to_string();
 
     skin.paragraph.align = Alignment::Left;
     skin.table.align = Alignment::Left;
+    for c in table.columns() {
+        let mut notes = Vec::new();
+        if table.is_required(c.name) {
+            notes.push("required");
+        }
+        if c.hidden {
+            notes.push("hidden");
+        }
+        let s = format!(
+            "|**{}**|{}|{}|{}|\n",
+            c.name,
+            c.kind,
+            c.description,
+            notes.join(", ")
+        );
+        text_template.push_str(&s);
+    }
+    text_template.push_str("|-");
 
-    println!("{}", skin.term_text(&text_template[..]));
-    println!("\n");
+    writeln!(out, "{}", skin.term_text(&text_template))?;
+    writeln!(out, "\n")
 }
 
-pub fn print_proc_map_schema() {
-    println!("");
-
-    let mut skin = MadSkin::default();
-    let mut text_template: String  = "|:-|:-|\n|**start_addr**|Start of mapping in process vm|\n|**end_addr**|End of mapping in process vm|\n|**permissions**|Permissions (rwx) for the section|\n|**size_maybe**|Size, maybe?|\n|**start_time**|Time at which the mapping occurred, I think |\n|**file_desc**|File Descriptor|\n|**file_name**|The name of the file mapped into memory if applicable|\n|-".to_string();
-
-    skin.paragraph.align = Alignment::Left;
-    skin.table.align = Alignment::Left;
-
-    println!("{}", skin.term_text(&text_template[..]));
-    println!("\n");
+/// Makes a value safe to put in a markdown table cell: line breaks would end
+/// the row and pipes would start a new cell, shifting everything after them.
+fn escape_cell(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\n' | '\r' | '\0' => out.push(' '),
+            '|' | '*' | '`' | '~' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
 }
 
-pub fn print_net_schema() {
-    println!("");
-
-    let mut skin = MadSkin::default();
-    let mut text_template: String  = "|:-|:-|\n|**uid**|uid of the owner of the network connection|\n|**local_address**|Our address|\n|**remote_address**|Their address|\n|**st**|Connection State|\n|**timeout**|Timeout|\n|**inode**|Inode|\n|**ref_cnt**|Reference Count|\n|**protocol**|Network Protocol (tcp or udp)|\n|-".to_string();
+/// A markdown table with the header first, the way most renderers expect.
+/// Without `headers` the header cells are left empty.
+fn to_plain_markdown(result: &ResultSet, headers: bool) -> String {
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut out = if headers {
+        line(result.names().iter().map(|x| escape_cell(x)).collect())
+    } else {
+        line(vec![String::new(); result.columns.len()])
+    };
+    out.push_str(&line(vec!["---".to_string(); result.columns.len()]));
+    for r in &result.rows {
+        out.push_str(&line(
+            r.iter().map(|x| escape_cell(&x.to_string())).collect(),
+        ));
+    }
+    out
+}
 
-    skin.paragraph.align = Alignment::Left;
-    skin.table.align = Alignment::Left;
+/// How tables are fitted into the terminal.
+#[derive(Clone, Debug)]
+pub struct Layout {
+    /// The widest any column can be, 0 for no limit.
+    pub max_width: usize,
+    /// Limits for particular columns, overriding `max_width`.
+    pub widths: Vec<(String, usize)>,
+    /// Wrap values that don't fit onto more lines instead of cutting them
+    /// short.
+    pub wrap: bool,
+    /// Show tables taller than the terminal in the pager.
+    pub pager: bool,
+}
 
-    println!("{}", skin.term_text(&text_template[..]));
-    println!("\n");
+impl Default for Layout {
+    fn default() -> Layout {
+        Layout {
+            max_width: 60,
+            widths: Vec::new(),
+            wrap: false,
+            pager: true,
+        }
+    }
 }
 
-pub fn print_os_version_schema() {
-    println!("");
+impl Layout {
+    fn limit(&self, column: &str) -> usize {
+        match self.widths.iter().find(|x| x.0 == column) {
+            Some((_, w)) => *w,
+            None => self.max_width,
+        }
+    }
+}
 
-    let mut skin = MadSkin::default();
-    let mut text_template: String  = "|:-|:-|\n|**kernel_version**|The version of the Linux kernel in use|\n|**build_id**|Hopefully the distro name?|\n|**gcc_version**|The version of the gcc compiler in use|\n|-".to_string();
+/// A cell's text with anything that would break the table turned to spaces.
+fn cell_text(s: &str) -> Vec<char> {
+    s.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
 
-    skin.paragraph.align = Alignment::Left;
-    skin.table.align = Alignment::Left;
+/// The lines a cell takes up at `width`: cut short with an ellipsis, or
+/// wrapped.
+fn fit_cell(text: &[char], width: usize, wrap: bool) -> Vec<String> {
+    if text.len() <= width {
+        return vec![text.iter().collect()];
+    }
+    if wrap && width > 0 {
+        return text.chunks(width).map(|x| x.iter().collect()).collect();
+    }
+    let mut cut: String = text[..width.saturating_sub(1)].iter().collect();
+    cut.push('…');
+    vec![cut]
+}
 
-    println!("{}", skin.term_text(&text_template[..]));
-    println!("\n");
+/// How wide each column should be: as wide as its values up to its limit,
+/// then narrowed widest first until the table fits in `total` characters.
+fn column_widths(natural: &[usize], limits: &[usize], total: usize) -> Vec<usize> {
+    const NARROWEST: usize = 3;
+    let mut widths: Vec<usize> = natural
+        .iter()
+        .zip(limits)
+        .map(|(n, l)| if *l > 0 { (*n).min(*l) } else { *n })
+        .collect();
+    // Each column has a border to its left, and the table one on the right.
+    let available = total.saturating_sub(widths.len() + 1);
+    while widths.iter().sum::<usize>() > available {
+        let (i, w) = match widths.iter().enumerate().max_by_key(|x| *x.1) {
+            Some((i, w)) if *w > NARROWEST => (i, *w),
+            _ => break,
+        };
+        widths[i] = w - 1;
+    }
+    widths
 }
 
-pub fn print_hash_table(entries: HashMap<String, String>) {
-    println!("");
+/// Draws a result as a table with box characters, at most `total`
+/// characters wide. With `colour` the borders are grey, the headers bold and
+/// row `i` is drawn in `styles[i]` if there is one; without it there are no
+/// escape codes at all, for writing to files and pipes.
+pub fn render_table(
+    result: &ResultSet,
+    headers: bool,
+    layout: &Layout,
+    total: usize,
+    colour: bool,
+    styles: &[Style],
+) -> Vec<String> {
+    let plain = Style::new();
+    let border = |s: String| match colour {
+        true => Colour::Fixed(239).paint(s).to_string(),
+        false => s,
+    };
+    let names: Vec<Vec<char>> = result.names().iter().map(|x| cell_text(x)).collect();
+    let rows: Vec<Vec<Vec<char>>> = result
+        .rows
+        .iter()
+        .map(|r| r.iter().map(|v| cell_text(&v.to_string())).collect())
+        .collect();
+
+    let natural: Vec<usize> = (0..names.len())
+        .map(|i| {
+            let values = rows.iter().map(|r| r[i].len());
+            let header = if headers { names[i].len() } else { 0 };
+            values.chain(std::iter::once(header)).max().unwrap_or(0)
+        })
+        .collect();
+    let limits: Vec<usize> = result.names().iter().map(|c| layout.limit(c)).collect();
+    let widths = column_widths(&natural, &limits, total);
+
+    let rule = |left: &str, mid: &str, right: &str| {
+        let parts: Vec<String> = widths.iter().map(|w| "─".repeat(*w)).collect();
+        border(format!("{}{}{}", left, parts.join(mid), right))
+    };
+    let row_lines = |cells: &[Vec<char>], style: Style| {
+        let fitted: Vec<Vec<String>> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| fit_cell(c, *w, layout.wrap))
+            .collect();
+        let height = fitted.iter().map(|x| x.len()).max().unwrap_or(1);
+        (0..height)
+            .map(|n| {
+                let mut line = border("│".to_string());
+                for (f, w) in fitted.iter().zip(&widths) {
+                    let text = f.get(n).map_or("", |x| x.as_str());
+                    let pad = " ".repeat(w - text.chars().count());
+                    line.push_str(&style.paint(format!("{}{}", text, pad)).to_string());
+                    line.push_str(&border("│".to_string()));
+                }
+                line
+            })
+            .collect::<Vec<String>>()
+    };
+
+    let mut lines = vec![rule("┌", "┬", "┐")];
+    if headers {
+        lines.extend(row_lines(&names, if colour { plain.bold() } else { plain }));
+        lines.push(rule("├", "┼", "┤"));
+    }
+    for (i, r) in rows.iter().enumerate() {
+        let style = match colour {
+            true => styles.get(i).copied().unwrap_or_default(),
+            false => plain,
+        };
+        lines.extend(row_lines(r, style));
+    }
+    lines.push(rule("└", "┴", "┘"));
+    lines
+}
 
-    let mut skin = MadSkin::default();
-    let mut text_template: String = "|:-|:-|\n".to_string();
+/// Prints a result. `terminal` is whether `out` is the terminal, so tables
+/// are coloured, fitted to its width and paged if they're too tall for it.
+pub fn print_result(
+    out: &mut dyn Write,
+    result: &ResultSet,
+    format: Format,
+    headers: bool,
+    layout: &Layout,
+    terminal: bool,
+) -> io::Result<()> {
+    match format {
+        Format::Table => print_data_table(out, result, headers, layout, terminal),
+        Format::Markdown => write!(out, "{}", to_plain_markdown(result, headers)),
+        _ => output::write_result(out, result, format, headers),
+    }?;
+    out.flush()
+}
 
-    skin.paragraph.align = Alignment::Left;
-    skin.table.align = Alignment::Left;
-    for (k, v) in &entries {
-        let s = format!("|**{}**|{}|\n", k, v);
-        text_template.push_str(&s);
+pub fn print_data_table(
+    out: &mut dyn Write,
+    result: &ResultSet,
+    headers: bool,
+    layout: &Layout,
+    terminal: bool,
+) -> io::Result<()> {
+    let size = if terminal {
+        crossterm::terminal::size().ok()
+    } else {
+        None
+    };
+    let width = size.map_or(usize::MAX, |(w, _)| w as usize);
+    let lines = render_table(result, headers, layout, width, terminal, &[]);
+    if let Some((_, height)) = size {
+        if layout.pager && lines.len() + 1 >= height as usize {
+            return pager::page(&lines);
+        }
     }
-    text_template.push_str("|-");
+    writeln!(out)?;
+    for l in lines {
+        writeln!(out, "{}", l)?;
+    }
+    writeln!(out)
+}
 
-    println!("{}", skin.term_text(&text_template[..]));
-    println!("\n");
+/// Paints `s` for stderr, leaving it plain when that isn't a terminal, e.g.
+/// when `-e` runs from cron.
+fn paint_stderr(style: Style, s: String) -> String {
+    match io::stderr().is_terminal() {
+        true => style.paint(s).to_string(),
+        false => s,
+    }
 }
 
-pub fn print_data_table(table: String) {
-    println!("");
-    let mut skin = MadSkin::default();
+/// Lists what the tables couldn't read, so a short result isn't a mystery.
+pub fn print_warnings(result: &ResultSet) {
+    const SHOWN: usize = 10;
+    if result.warnings.is_empty() {
+        return;
+    }
+    eprintln!(
+        "{}",
+        paint_stderr(
+            Colour::Yellow.normal(),
+            format!("⚠ {} warning(s):", result.warnings.len())
+        )
+    );
+    for w in result.warnings.iter().take(SHOWN) {
+        eprintln!("  {}", w);
+    }
+    if result.warnings.len() > SHOWN {
+        eprintln!("  ... and {} more", result.warnings.len() - SHOWN);
+    }
+}
 
-    skin.paragraph.align = Alignment::Left;
-    skin.table.align = Alignment::Left;
-    let text_template = format!("{}|-", table);
-    println!("{}", skin.term_text(&text_template[..]));
-    println!("\n");
+/// Prints why a query failed, with a caret under the part of it at fault.
+pub fn print_error(error: &QueryError, sql: &str) {
+    eprintln!("⚠ Oh Nos! {}", error);
+    let span = match error.span(sql) {
+        Some(s) => s,
+        None => return,
+    };
+    // Only show the line the error is on, for queries over several lines.
+    let line_start = sql[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = sql[span.start..]
+        .find('\n')
+        .map_or(sql.len(), |i| span.start + i);
+    let line = &sql[line_start..line_end];
+    let indent = sql[line_start..span.start].chars().count();
+    let width = sql[span.start..span.end.min(line_end)]
+        .chars()
+        .count()
+        .max(1);
+    eprintln!("  {}", line);
+    eprintln!(
+        "  {}{}",
+        " ".repeat(indent),
+        paint_stderr(Colour::Red.bold(), "^".repeat(width))
+    );
 }
 
 pub fn dog() {
-    println!("");
-    let lesser_dog =  "
+    println!();
+    let lesser_dog = "
     ░░░░░░░░░░░▄▀▄▀▀▀▀▄▀▄░░░░░░░░░░░░░░░░░░
     ░░░░░░░░░░░█░░░░░░░░▀▄░░░░░░▄░░░░░░░░░░
     ░░░░░░░░░░█░░▀░░▀░░░░░▀▄▄░░█░█░░░░░░░░░
@@ -186,4 +445,4 @@ pub fn dog() {
 
     println!("{}", lesser_dog);
     println!("\n");
-}
\ No newline at end of file
+}
@@ -1,12 +1,10 @@
+#[allow(clippy::module_inception)]
 mod app;
-mod engine;
+mod cli;
+mod commands;
+mod editor;
 mod interface;
-
-pub use self::app::mainloop;
-pub use self::interface::{
-    print_banner, print_data_table, print_hash_table, print_help, print_prompt,print_fs_schema,print_procs_schema,print_os_version_schema,dog,print_net_schema,print_proc_map_schema
-};
-//pub use self::engine::{query_folder, query_procs, search_proc_memory, FileItem, ProcItem, do_get_os_version_info};
-pub use self::engine::{
-    do_get_os_version_info, query_dir, query_procs, query_proc_maps, FilterItem, FilterItems, FilterOp, query_net
-};
+mod output;
+mod pager;
+mod watch;
+pub use self::app::run;
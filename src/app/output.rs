@@ -0,0 +1,289 @@
+use rusty_osquery::tables::ColumnType;
+use rusty_osquery::{ResultColumn, ResultSet, Value};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// How query results are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+    /// A table drawn with box characters, for reading in a terminal.
+    #[default]
+    Table,
+    /// A plain markdown table, for pasting into docs and issues.
+    Markdown,
+    /// One JSON array of objects.
+    Json,
+    /// One JSON object per line.
+    JsonLines,
+    Csv,
+    Tsv,
+    /// Every value on its own `column = value` line, like osquery's line mode.
+    Line,
+}
+
+const FORMATS: [(&str, Format); 7] = [
+    ("table", Format::Table),
+    ("markdown", Format::Markdown),
+    ("json", Format::Json),
+    ("ndjson", Format::JsonLines),
+    ("csv", Format::Csv),
+    ("tsv", Format::Tsv),
+    ("line", Format::Line),
+];
+
+impl Format {
+    /// The names formats can be picked by, for help text.
+    pub fn names() -> String {
+        let names: Vec<&str> = FORMATS.iter().map(|x| x.0).collect();
+        names.join(", ")
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        let s = s.to_lowercase();
+        let s = match s.as_str() {
+            "md" => "markdown",
+            "jsonl" | "json_lines" => "ndjson",
+            x => x,
+        };
+        FORMATS
+            .iter()
+            .find(|x| x.0 == s)
+            .map(|x| x.1)
+            .ok_or_else(|| format!("Unknown format {}, expected one of {}", s, Format::names()))
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = FORMATS.iter().find(|x| x.1 == *self).map_or("", |x| x.0);
+        write!(f, "{}", name)
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// A value as JSON, typed by its column where the column's type is known and
+/// by the value itself otherwise, so an integer column stays a number even
+/// when a value was read as text.
+fn json_value(v: &Value, kind: Option<ColumnType>) -> String {
+    match (kind, v) {
+        (_, Value::Null) => "null".to_string(),
+        (Some(ColumnType::Text | ColumnType::Timestamp), _) => json_string(&v.to_string()),
+        (Some(ColumnType::Integer), Value::Text(s)) if s.trim().parse::<i64>().is_ok() => {
+            s.trim().to_string()
+        }
+        (Some(ColumnType::Bool), Value::Integer(i)) => (*i != 0).to_string(),
+        (_, Value::Integer(i)) => i.to_string(),
+        (_, Value::Real(r)) if r.is_finite() => r.to_string(),
+        (_, Value::Real(_)) => "null".to_string(),
+        (_, Value::Bool(b)) => b.to_string(),
+        _ => json_string(&v.to_string()),
+    }
+}
+
+fn json_object(columns: &[ResultColumn], row: &[Value]) -> String {
+    let fields: Vec<String> = columns
+        .iter()
+        .zip(row)
+        .map(|(c, v)| format!("{}: {}", json_string(&c.name), json_value(v, c.kind)))
+        .collect();
+    format!("{{{}}}", fields.join(", "))
+}
+
+/// Quotes a CSV field if it needs it, as RFC 4180 describes. NULL is empty.
+fn csv_field(v: &Value) -> String {
+    let s = match v {
+        Value::Null => return String::new(),
+        v => v.to_string(),
+    };
+    if s.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s
+    }
+}
+
+/// Escapes a TSV field so tabs and newlines can't break the row. NULL is
+/// empty.
+fn tsv_field(v: &Value) -> String {
+    let s = match v {
+        Value::Null => return String::new(),
+        v => v.to_string(),
+    };
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Writes a result in one of the machine readable formats. The table and
+/// markdown formats are drawn by `interface` instead. `headers` is whether
+/// CSV and TSV start with a row of column names.
+pub fn write_result(
+    out: &mut dyn Write,
+    result: &ResultSet,
+    format: Format,
+    headers: bool,
+) -> io::Result<()> {
+    let columns = &result.columns;
+    match format {
+        Format::Json => {
+            writeln!(out, "[")?;
+            for (i, r) in result.rows.iter().enumerate() {
+                let sep = if i + 1 < result.rows.len() { "," } else { "" };
+                writeln!(out, "  {}{}", json_object(columns, r), sep)?;
+            }
+            writeln!(out, "]")?;
+        }
+        Format::JsonLines => {
+            for r in &result.rows {
+                writeln!(out, "{}", json_object(columns, r))?;
+            }
+        }
+        Format::Csv => {
+            let header: Vec<String> = columns
+                .iter()
+                .map(|c| csv_field(&Value::Text(c.name.clone())))
+                .collect();
+            if headers {
+                write!(out, "{}\r\n", header.join(","))?;
+            }
+            for r in &result.rows {
+                let fields: Vec<String> = r.iter().map(csv_field).collect();
+                write!(out, "{}\r\n", fields.join(","))?;
+            }
+        }
+        Format::Tsv => {
+            let header: Vec<String> = columns
+                .iter()
+                .map(|c| tsv_field(&Value::Text(c.name.clone())))
+                .collect();
+            if headers {
+                writeln!(out, "{}", header.join("\t"))?;
+            }
+            for r in &result.rows {
+                let fields: Vec<String> = r.iter().map(tsv_field).collect();
+                writeln!(out, "{}", fields.join("\t"))?;
+            }
+        }
+        Format::Line => {
+            let width = columns
+                .iter()
+                .map(|c| c.name.chars().count())
+                .max()
+                .unwrap_or(0);
+            for (i, r) in result.rows.iter().enumerate() {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                for (c, v) in columns.iter().zip(r) {
+                    writeln!(out, "{:>width$} = {}", c.name, v, width = width)?;
+                }
+            }
+        }
+        Format::Table | Format::Markdown => (),
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    #[test]
+    fn csv_quotes_only_when_needed() {
+        assert_eq!(csv_field(&text("plain")), "plain");
+        assert_eq!(csv_field(&text("a,b")), "\"a,b\"");
+        assert_eq!(csv_field(&text("say \"hi\"")), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field(&text("two\r\nlines")), "\"two\r\nlines\"");
+        assert_eq!(csv_field(&text("cr\r")), "\"cr\r\"");
+        assert_eq!(csv_field(&Value::Null), "");
+        assert_eq!(csv_field(&text("")), "");
+        assert_eq!(csv_field(&Value::Integer(-3)), "-3");
+    }
+
+    #[test]
+    fn tsv_escapes_what_would_break_a_row() {
+        assert_eq!(tsv_field(&text("a\tb\nc\rd")), "a\\tb\\nc\\rd");
+        assert_eq!(tsv_field(&text("back\\slash")), "back\\\\slash");
+        assert_eq!(tsv_field(&text("a,\"b\"")), "a,\"b\"");
+        assert_eq!(tsv_field(&Value::Null), "");
+    }
+
+    #[test]
+    fn json_strings_escape_control_characters() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(json_string("\"q\" \\"), "\"\\\"q\\\" \\\\\"");
+        assert_eq!(json_string("a\nb\rc\td"), "\"a\\nb\\rc\\td\"");
+        assert_eq!(
+            json_string("\u{0}\u{1b}\u{1f}"),
+            "\"\\u0000\\u001b\\u001f\""
+        );
+        assert_eq!(json_string("ünï"), "\"ünï\"");
+    }
+
+    #[test]
+    fn json_values_are_typed_by_column() {
+        let int = Some(ColumnType::Integer);
+        assert_eq!(json_value(&Value::Null, int), "null");
+        assert_eq!(json_value(&Value::Null, Some(ColumnType::Text)), "null");
+        assert_eq!(json_value(&text(" 42 "), int), "42");
+        assert_eq!(json_value(&text("n/a"), int), "\"n/a\"");
+        assert_eq!(
+            json_value(&Value::Integer(7), Some(ColumnType::Text)),
+            "\"7\""
+        );
+        assert_eq!(
+            json_value(&Value::Integer(1), Some(ColumnType::Bool)),
+            "true"
+        );
+        assert_eq!(json_value(&Value::Bool(false), None), "false");
+        assert_eq!(json_value(&Value::Real(1.5), None), "1.5");
+        assert_eq!(json_value(&Value::Real(f64::NAN), None), "null");
+        assert_eq!(json_value(&text("x"), None), "\"x\"");
+    }
+
+    #[test]
+    fn csv_rows_end_in_crlf() {
+        let result = ResultSet {
+            columns: vec![ResultColumn {
+                name: "a,b".to_string(),
+                kind: None,
+                table: None,
+            }],
+            rows: vec![vec![Value::Null], vec![text("x\ny")]],
+            warnings: Vec::new(),
+        };
+        let mut out = Vec::new();
+        write_result(&mut out, &result, Format::Csv, true).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\"a,b\"\r\n\r\n\"x\ny\"\r\n"
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use regex::Regex;
+use std::io::{self, Write};
+
+const HELP: &str = "q quit, arrows/j/k scroll, space/b page, g/G ends, / search, n/N next/previous";
+
+/// Puts the terminal back the way it was, even if paging fails part way.
+pub struct Screen;
+
+impl Screen {
+    pub fn enter() -> io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// The text of a line without its colours, for searching.
+fn plain(line: &str) -> String {
+    let re = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+    re.replace_all(line, "").to_lowercase()
+}
+
+struct Pager<'a> {
+    lines: &'a [String],
+    plain: Vec<String>,
+    top: usize,
+    search: String,
+    status: String,
+}
+
+impl Pager<'_> {
+    fn draw(&self, out: &mut impl Write, width: usize, height: usize) -> io::Result<()> {
+        queue!(out, MoveTo(0, 0), Clear(ClearType::All))?;
+        for (i, line) in self.lines.iter().skip(self.top).take(height).enumerate() {
+            queue!(out, MoveTo(0, i as u16), Print(line))?;
+        }
+        let last = (self.top + height).min(self.lines.len());
+        let status = if self.status.is_empty() {
+            format!(
+                "lines {}-{} of {} ({})",
+                self.top + 1,
+                last,
+                self.lines.len(),
+                HELP
+            )
+        } else {
+            self.status.clone()
+        };
+        queue!(
+            out,
+            MoveTo(0, height as u16),
+            SetAttribute(Attribute::Reverse),
+            Print(status.chars().take(width).collect::<String>()),
+            SetAttribute(Attribute::Reset)
+        )?;
+        out.flush()
+    }
+
+    /// Moves to the next line after (or before) the top one that matches the
+    /// search.
+    fn find(&mut self, forward: bool) {
+        if self.search.is_empty() {
+            return;
+        }
+        let found = if forward {
+            (self.top + 1..self.plain.len()).find(|i| self.plain[*i].contains(&self.search))
+        } else {
+            (0..self.top)
+                .rev()
+                .find(|i| self.plain[*i].contains(&self.search))
+        };
+        match found {
+            Some(i) => self.top = i,
+            None => self.status = format!("Pattern not found: {}", self.search),
+        }
+    }
+
+    /// Reads a search pattern on the status line and moves to the first
+    /// match. Esc gives up.
+    fn search(&mut self, out: &mut impl Write, height: usize) -> io::Result<()> {
+        let mut pattern = String::new();
+        loop {
+            queue!(
+                out,
+                MoveTo(0, height as u16),
+                Clear(ClearType::CurrentLine),
+                Print(format!("/{}", pattern))
+            )?;
+            out.flush()?;
+            if let Event::Key(KeyEvent { code, .. }) = event::read()? {
+                match code {
+                    KeyCode::Enter => break,
+                    KeyCode::Esc => return Ok(()),
+                    KeyCode::Backspace => {
+                        pattern.pop();
+                    }
+                    KeyCode::Char(c) => pattern.push(c),
+                    _ => (),
+                }
+            }
+        }
+        self.search = pattern.to_lowercase();
+        self.find(true);
+        Ok(())
+    }
+}
+
+/// Shows `lines` a screen at a time until the user quits.
+pub fn page(lines: &[String]) -> io::Result<()> {
+    let _screen = Screen::enter()?;
+    let mut out = io::stdout();
+    let mut pager = Pager {
+        lines,
+        plain: lines.iter().map(|x| plain(x)).collect(),
+        top: 0,
+        search: String::new(),
+        status: String::new(),
+    };
+    loop {
+        let (width, height) = terminal::size()?;
+        let height = height.max(2) as usize - 1;
+        let bottom = lines.len().saturating_sub(height);
+        pager.top = pager.top.min(bottom);
+        pager.draw(&mut out, width as usize, height)?;
+        pager.status.clear();
+
+        let key = match event::read()? {
+            Event::Key(k) => k,
+            _ => continue,
+        };
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Enter => pager.top += 1,
+            KeyCode::Up | KeyCode::Char('k') => pager.top = pager.top.saturating_sub(1),
+            KeyCode::PageDown | KeyCode::Char(' ') | KeyCode::Char('f') => pager.top += height,
+            KeyCode::PageUp | KeyCode::Char('b') => pager.top = pager.top.saturating_sub(height),
+            KeyCode::Home | KeyCode::Char('g') => pager.top = 0,
+            KeyCode::End | KeyCode::Char('G') => pager.top = bottom,
+            KeyCode::Char('/') => pager.search(&mut out, height)?,
+            KeyCode::Char('n') => pager.find(true),
+            KeyCode::Char('N') => pager.find(false),
+            _ => (),
+        }
+    }
+    Ok(())
+}
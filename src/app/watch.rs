@@ -0,0 +1,347 @@
+use super::interface::{render_table, Layout};
+use super::pager::Screen;
+use ansi_term::{Colour, Style};
+use chrono::Local;
+use crossterm::cursor::MoveTo;
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::queue;
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType};
+use rusty_osquery::{Engine, ResultColumn, ResultSet, Value};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+const HELP: &str =
+    "q quit, space pause, left/right pick a column, s sort by it, r refresh, up/down scroll";
+
+/// Columns that together tell rows apart between runs, e.g. `pid` and `fd`
+/// for a process's open files.
+const KEYS: [&str; 5] = ["pid", "fd", "index", "start_addr", "inode"];
+
+/// Used instead when none of `KEYS` was selected, in the order they're tried.
+const NAMES: [&str; 2] = ["path", "name"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Change {
+    Same,
+    Added,
+    Changed,
+    Removed,
+}
+
+/// What the watch screen shows and how.
+struct View {
+    columns: Vec<ResultColumn>,
+    /// The rows from the last run, and the ones gone since the run before.
+    rows: Vec<(Vec<Value>, Change)>,
+    selected: usize,
+    sort: Option<(usize, bool)>,
+    paused: bool,
+    scroll: usize,
+    error: Option<String>,
+    updated: String,
+}
+
+/// Names each row by its key columns, or by all of it if there aren't any.
+/// Rows with the same key are told apart by how many came before.
+fn keyed(columns: &[ResultColumn], rows: &[Vec<Value>]) -> Vec<String> {
+    let bare = |c: &ResultColumn| c.name.rsplit('.').next().unwrap_or(&c.name).to_string();
+    let mut key: Vec<usize> = (0..columns.len())
+        .filter(|i| KEYS.contains(&bare(&columns[*i]).as_str()))
+        .collect();
+    if key.is_empty() {
+        key.extend(
+            NAMES
+                .iter()
+                .find_map(|k| columns.iter().position(|c| bare(c) == *k)),
+        );
+    }
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    rows.iter()
+        .map(|r| {
+            let k = if key.is_empty() {
+                format!("{:?}", r)
+            } else {
+                let values: Vec<String> = key.iter().map(|i| r[*i].to_string()).collect();
+                values.join("|")
+            };
+            let n = seen.entry(k.clone()).or_insert(0);
+            *n += 1;
+            format!("{}#{}", k, n)
+        })
+        .collect()
+}
+
+impl View {
+    /// Takes the rows of a new run and marks how each differs from the last.
+    fn update(&mut self, result: ResultSet) {
+        let old: Vec<Vec<Value>> = self
+            .rows
+            .drain(..)
+            .filter(|x| x.1 != Change::Removed)
+            .map(|x| x.0)
+            .collect();
+        let first = self.columns.is_empty();
+        let old_keys: HashMap<String, Vec<Value>> =
+            keyed(&self.columns, &old).into_iter().zip(old).collect();
+        let new_keys = keyed(&result.columns, &result.rows);
+
+        for (k, row) in new_keys.iter().zip(result.rows) {
+            let change = match old_keys.get(k) {
+                _ if first => Change::Same,
+                None => Change::Added,
+                Some(o) if *o != row => Change::Changed,
+                Some(_) => Change::Same,
+            };
+            self.rows.push((row, change));
+        }
+        for (k, row) in old_keys {
+            if !new_keys.contains(&k) {
+                self.rows.push((row, Change::Removed));
+            }
+        }
+        self.columns = result.columns;
+        self.selected = self.selected.min(self.columns.len().saturating_sub(1));
+        self.sort_rows();
+    }
+
+    fn sort_rows(&mut self) {
+        if let Some((i, ascending)) = self.sort {
+            self.rows.sort_by(|a, b| {
+                let o = a.0[i].sort_cmp(&b.0[i]);
+                if ascending {
+                    o
+                } else {
+                    o.reverse()
+                }
+            });
+        }
+    }
+
+    fn draw(&self, out: &mut impl Write, sql: &str, layout: &Layout) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        let mut columns = self.columns.clone();
+        if let Some((i, ascending)) = self.sort {
+            columns[i].name.push(if ascending { '▲' } else { '▼' });
+        }
+        if let Some(c) = columns.get_mut(self.selected) {
+            c.name = format!("[{}]", c.name);
+        }
+        let state = if self.paused { "paused" } else { "watching" };
+        let header = [
+            format!("{} {} (updated {})", state, sql, self.updated),
+            format!(
+                "{} row(s), {} {} {}",
+                self.rows.len(),
+                Colour::Green.paint("added"),
+                Colour::Yellow.paint("changed"),
+                Colour::Red.paint("removed"),
+            ),
+            match &self.error {
+                Some(e) => Colour::Red.paint(format!("⚠ Oh Nos! {}", e)).to_string(),
+                None => HELP.to_string(),
+            },
+            String::new(),
+        ];
+
+        // Rows don't wrap here, so each takes one line. The table adds four
+        // more: the top rule, the column names, the rule under them and the
+        // bottom rule.
+        let shown = height.saturating_sub(header.len() + 4);
+        let visible: Vec<&(Vec<Value>, Change)> =
+            self.rows.iter().skip(self.scroll).take(shown).collect();
+        let result = ResultSet {
+            columns,
+            rows: visible.iter().map(|x| x.0.clone()).collect(),
+            warnings: Vec::new(),
+        };
+        let styles: Vec<Style> = visible
+            .iter()
+            .map(|x| match x.1 {
+                Change::Same => Style::new(),
+                Change::Added => Colour::Green.bold(),
+                Change::Changed => Colour::Yellow.bold(),
+                Change::Removed => Colour::Red.strikethrough(),
+            })
+            .collect();
+        let lines = render_table(&result, true, layout, width, true, &styles);
+
+        queue!(out, MoveTo(0, 0), Clear(ClearType::All))?;
+        for (i, line) in header.iter().chain(lines.iter()).take(height).enumerate() {
+            queue!(out, MoveTo(0, i as u16), Print(line))?;
+        }
+        out.flush()
+    }
+}
+
+fn run_screen(
+    engine: &Engine,
+    sql: &str,
+    interval: Duration,
+    layout: &Layout,
+    view: &mut View,
+) -> io::Result<()> {
+    let _screen = Screen::enter()?;
+    let mut out = io::stdout();
+    let mut next = Instant::now() + interval;
+    loop {
+        view.draw(&mut out, sql, layout)?;
+        let wait = if view.paused {
+            Duration::from_secs(3600)
+        } else {
+            next.saturating_duration_since(Instant::now())
+        };
+        let mut refresh = false;
+        if event::poll(wait)? {
+            let key = match event::read()? {
+                Event::Key(k) => k,
+                _ => continue,
+            };
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                KeyCode::Char(' ') | KeyCode::Char('p') => view.paused = !view.paused,
+                KeyCode::Left => view.selected = view.selected.saturating_sub(1),
+                KeyCode::Right if view.selected + 1 < view.columns.len() => view.selected += 1,
+                KeyCode::Char('s') | KeyCode::Enter => {
+                    view.sort = match view.sort {
+                        Some((i, asc)) if i == view.selected => Some((i, !asc)),
+                        _ => Some((view.selected, true)),
+                    };
+                    view.sort_rows();
+                }
+                KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+                KeyCode::Down if view.scroll + 1 < view.rows.len() => view.scroll += 1,
+                KeyCode::Char('r') => refresh = true,
+                _ => (),
+            }
+        }
+        if refresh || (!view.paused && Instant::now() >= next) {
+            match engine.query(sql) {
+                Ok(r) => {
+                    view.update(r);
+                    view.error = None;
+                }
+                // Keep showing the last rows, it may work next time.
+                Err(e) => view.error = Some(e.to_string()),
+            }
+            view.updated = Local::now().format("%H:%M:%S").to_string();
+            next = Instant::now() + interval;
+        }
+    }
+    Ok(())
+}
+
+/// Runs `sql` every `interval` in a full screen view until the user quits.
+/// Fails without taking over the screen if the query doesn't run at all.
+pub fn watch(
+    engine: &Engine,
+    sql: &str,
+    interval: Duration,
+    layout: &Layout,
+) -> Result<(), String> {
+    let first = engine.query(sql).map_err(|e| e.to_string())?;
+    let mut layout = layout.clone();
+    layout.wrap = false;
+    let mut view = View {
+        columns: Vec::new(),
+        rows: Vec::new(),
+        selected: 0,
+        sort: None,
+        paused: false,
+        scroll: 0,
+        error: None,
+        updated: Local::now().format("%H:%M:%S").to_string(),
+    };
+    view.update(first);
+    run_screen(engine, sql, interval, &layout, &mut view).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(names: &[&str]) -> Vec<ResultColumn> {
+        names
+            .iter()
+            .map(|x| ResultColumn {
+                name: x.to_string(),
+                kind: None,
+                table: None,
+            })
+            .collect()
+    }
+
+    fn row(values: &[i64]) -> Vec<Value> {
+        values.iter().map(|x| Value::Integer(*x)).collect()
+    }
+
+    fn view() -> View {
+        View {
+            columns: Vec::new(),
+            rows: Vec::new(),
+            selected: 0,
+            sort: None,
+            paused: false,
+            scroll: 0,
+            error: None,
+            updated: String::new(),
+        }
+    }
+
+    fn result(names: &[&str], rows: &[&[i64]]) -> ResultSet {
+        ResultSet {
+            columns: columns(names),
+            rows: rows.iter().map(|x| row(x)).collect(),
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn keyed_on_every_key_column() {
+        let rows = [row(&[42, 0, 7]), row(&[42, 1, 8]), row(&[42, 1, 9])];
+        assert_eq!(
+            keyed(&columns(&["p.pid", "fd", "pos"]), &rows),
+            vec!["42|0#1", "42|1#1", "42|1#2"]
+        );
+    }
+
+    #[test]
+    fn keyed_falls_back_to_names_then_the_row() {
+        let rows = [row(&[1, 2])];
+        assert_eq!(keyed(&columns(&["name", "path"]), &rows), vec!["2#1"]);
+        assert_eq!(
+            keyed(&columns(&["a", "b"]), &rows),
+            vec!["[Integer(1), Integer(2)]#1"]
+        );
+    }
+
+    #[test]
+    fn update_marks_only_what_changed() {
+        let mut view = view();
+        let names = ["pid", "fd", "pos"];
+        view.update(result(&names, &[&[42, 0, 0], &[42, 1, 0], &[42, 2, 0]]));
+        assert!(view.rows.iter().all(|x| x.1 == Change::Same));
+
+        // fd 1 closes, fd 2 moves on and fd 3 opens.
+        view.update(result(&names, &[&[42, 0, 0], &[42, 2, 5], &[42, 3, 0]]));
+        let changes: Vec<(Vec<Value>, Change)> = view.rows.clone();
+        assert_eq!(
+            changes,
+            vec![
+                (row(&[42, 0, 0]), Change::Same),
+                (row(&[42, 2, 5]), Change::Changed),
+                (row(&[42, 3, 0]), Change::Added),
+                (row(&[42, 1, 0]), Change::Removed),
+            ]
+        );
+
+        // What was removed is dropped on the next run.
+        view.update(result(&names, &[&[42, 0, 0]]));
+        assert_eq!(view.rows.len(), 3);
+        assert_eq!(view.rows[0], (row(&[42, 0, 0]), Change::Same));
+        assert!(view.rows[1..].iter().all(|x| x.1 == Change::Removed));
+    }
+}
@@ -0,0 +1,248 @@
+use super::engine::Filter;
+use super::error::QueryError;
+use super::sql::{BinaryOperator, Expr};
+use super::tables::Table;
+use super::value::Value;
+use std::fmt;
+
+/// The ways a constraint on an index column can be written in a where clause.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
+    Eq,
+    In,
+    Like,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operator::Eq => write!(f, "="),
+            Operator::In => write!(f, "in"),
+            Operator::Like => write!(f, "like"),
+        }
+    }
+}
+
+/// A column a table can use to cut down what it reads, like the pid of
+/// `proc_maps`. A required column has to be constrained before the table
+/// can be scanned at all.
+pub struct IndexColumn {
+    pub name: &'static str,
+    pub required: bool,
+    pub operators: &'static [Operator],
+}
+
+/// A condition on an index column, handed to the table while it scans.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constraint {
+    /// The column is one of these values, from `=` or `in`.
+    Values(Vec<Value>),
+    /// The column matches this `like` pattern.
+    Like(Value),
+}
+
+impl Constraint {
+    pub fn matches(&self, v: &Value) -> bool {
+        match self {
+            Constraint::Values(values) => values
+                .iter()
+                .any(|x| x.compare(v) == Some(std::cmp::Ordering::Equal)),
+            Constraint::Like(pattern) => v.like(pattern) == Some(true),
+        }
+    }
+}
+
+/// The constraints pushed down into one table scan.
+#[derive(Debug, Default)]
+pub struct Constraints {
+    found: Vec<(&'static str, Constraint)>,
+}
+
+impl Constraints {
+    pub fn get(&self, column: &str) -> Option<&Constraint> {
+        self.found
+            .iter()
+            .find(|(name, _)| *name == column)
+            .map(|(_, c)| c)
+    }
+}
+
+fn literal_value(expr: &Expr) -> Option<Value> {
+    match expr {
+        Expr::Literal(l) => Some(Value::from(l.clone())),
+        _ => None,
+    }
+}
+
+/// Reads a condition as a constraint on `column`. The flag says whether the
+/// constraint says all the condition does, so the condition can be dropped.
+fn constraint_on(expr: &Expr, column: &IndexColumn) -> Option<(Constraint, bool)> {
+    let is_column = |e: &Expr| matches!(e, Expr::Column(c) if c == column.name);
+    let (op, constraint) = match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Eq,
+            right,
+        } => {
+            let v = match (left.as_ref(), right.as_ref()) {
+                (l, r) if is_column(l) => literal_value(r)?,
+                (l, r) if is_column(r) => literal_value(l)?,
+                _ => return None,
+            };
+            (Operator::Eq, Constraint::Values(vec![v]))
+        }
+        Expr::InList {
+            expr,
+            list,
+            negated: false,
+        } if is_column(expr) => {
+            let values: Option<Vec<Value>> = list.iter().map(literal_value).collect();
+            (Operator::In, Constraint::Values(values?))
+        }
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Like,
+            right,
+        } if is_column(left) => (Operator::Like, Constraint::Like(literal_value(right)?)),
+        _ => return None,
+    };
+    if !column.operators.contains(&op) {
+        return None;
+    }
+    let exact = op != Operator::Like;
+    Some((constraint, exact))
+}
+
+/// True if the conditions constrain `column` in a way the table accepts.
+pub fn is_constrained(conjuncts: &[Expr], column: &IndexColumn) -> bool {
+    conjuncts.iter().any(|x| constraint_on(x, column).is_some())
+}
+
+fn describe_operators(operators: &[Operator]) -> String {
+    let names: Vec<String> = operators.iter().map(|x| x.to_string()).collect();
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        _ => names.join(""),
+    }
+}
+
+/// Pulls the constraints on a table's index columns out of the where clause.
+/// Conditions the constraint fully covers are removed from `filters`, the
+/// rest stay to be checked on each row. Fails if a required column is missing.
+pub fn push_down(table: &dyn Table, filters: &mut Filter) -> Result<Constraints, QueryError> {
+    let mut constraints = Constraints::default();
+    for column in table.index_columns() {
+        let found = filters
+            .conjuncts
+            .iter()
+            .enumerate()
+            .find_map(|(i, x)| constraint_on(x, column).map(|(c, exact)| (i, c, exact)));
+        match found {
+            Some((i, c, exact)) => {
+                if exact {
+                    filters.conjuncts.remove(i);
+                }
+                constraints.found.push((column.name, c));
+            }
+            None if column.required => {
+                return Err(QueryError::MissingConstraint {
+                    table: table.name().to_string(),
+                    column: column.name.to_string(),
+                    operators: describe_operators(column.operators),
+                })
+            }
+            None => (),
+        }
+    }
+    Ok(constraints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::parse_statement;
+    use crate::tables;
+
+    /// The where clause of `sql`, split into its conjuncts.
+    fn filter(sql: &str) -> Filter {
+        Filter::new(parse_statement(sql).unwrap().selection.as_ref())
+    }
+
+    fn ints(values: &[i64]) -> Constraint {
+        Constraint::Values(values.iter().map(|x| Value::Integer(*x)).collect())
+    }
+
+    #[test]
+    fn exact_constraints_leave_the_filter() {
+        let table = tables::find("proc_maps").unwrap();
+        let mut filters = filter("select * from t where pid = 42 and inode > 0");
+        let constraints = push_down(table, &mut filters).unwrap();
+        assert_eq!(constraints.get("pid"), Some(&ints(&[42])));
+        assert_eq!(filters.conjuncts.len(), 1);
+        assert_eq!(filters.conjuncts[0].to_string(), "inode > 0");
+
+        let mut filters = filter("select * from t where 42 = pid");
+        let constraints = push_down(table, &mut filters).unwrap();
+        assert_eq!(constraints.get("pid"), Some(&ints(&[42])));
+        assert!(filters.conjuncts.is_empty());
+    }
+
+    #[test]
+    fn like_constraints_stay_in_the_filter() {
+        let table = tables::find("proc_maps").unwrap();
+        let mut filters = filter("select * from t where pid like '4%'");
+        let constraints = push_down(table, &mut filters).unwrap();
+        assert_eq!(
+            constraints.get("pid"),
+            Some(&Constraint::Like(Value::Text("4%".to_string())))
+        );
+        assert_eq!(filters.conjuncts.len(), 1);
+    }
+
+    #[test]
+    fn in_lists_keep_every_value() {
+        // Duplicates and pids that don't exist are for the table to sort out.
+        let table = tables::find("procs").unwrap();
+        let mut filters = filter("select * from t where pid in (42, 42, 99999)");
+        let constraints = push_down(table, &mut filters).unwrap();
+        assert_eq!(constraints.get("pid"), Some(&ints(&[42, 42, 99999])));
+        assert!(filters.conjuncts.is_empty());
+        assert!(!ints(&[42, 99999]).matches(&Value::Integer(7)));
+    }
+
+    #[test]
+    fn conditions_that_arent_constraints() {
+        let table = tables::find("procs").unwrap();
+        for sql in [
+            "select * from t where pid = 1 or pid = 2",
+            "select * from t where pid not in (1, 2)",
+            "select * from t where pid = ppid",
+            "select * from t where pid > 1",
+        ] {
+            let mut filters = filter(sql);
+            let constraints = push_down(table, &mut filters).unwrap();
+            assert_eq!(constraints.get("pid"), None, "{}", sql);
+            assert_eq!(filters.conjuncts.len(), 1, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn missing_constraints() {
+        let fs = tables::find("fs").unwrap();
+        let e = push_down(fs, &mut filter("select * from t where name = 'x'")).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Table fs needs a constraint on path using = or in, e.g. where path = ..."
+        );
+        // fs doesn't take like, so it isn't a constraint there.
+        let e = push_down(fs, &mut filter("select * from t where path like '/%'")).unwrap_err();
+        assert!(matches!(e, QueryError::MissingConstraint { .. }));
+
+        let maps = tables::find("proc_maps").unwrap();
+        let e = push_down(maps, &mut filter("select * from t")).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Table proc_maps needs a constraint on pid using =, in or like, e.g. where pid = ..."
+        );
+    }
+}
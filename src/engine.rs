@@ -0,0 +1,499 @@
+use super::aggregate::{check_functions, group_rows, Group};
+use super::constraint::push_down;
+use super::error::{QueryError, Warning};
+use super::join;
+use super::sql::{
+    parse_statement, BinaryOperator, Expr, Literal, OrderByExpr, SelectItem, Statement,
+};
+use super::tables::{self, ColumnType, Context, Roots, Row, Table};
+use super::value::Value;
+use std::cmp::Ordering;
+use std::path::PathBuf;
+
+pub trait HasLookup {
+    /// The value of a column in this row, NULL if there is no such column.
+    fn lookup(&self, attribute: &str) -> Value;
+
+    /// The value of an aggregate function over the rows behind this one,
+    /// NULL unless this row stands for a group.
+    fn aggregate(&self, _func: &Expr) -> Value {
+        Value::Null
+    }
+}
+
+impl<T: HasLookup> HasLookup for &T {
+    fn lookup(&self, attribute: &str) -> Value {
+        (*self).lookup(attribute)
+    }
+
+    fn aggregate(&self, func: &Expr) -> Value {
+        (*self).aggregate(func)
+    }
+}
+
+/// A result row built from the select list. Looking up an alias or a select
+/// expression gives its value, anything else falls through to the source row.
+struct ProjectedRow<R> {
+    names: Vec<String>,
+    values: Vec<Value>,
+    source: R,
+}
+
+impl<R: HasLookup> HasLookup for ProjectedRow<R> {
+    fn lookup(&self, attribute: &str) -> Value {
+        match self.names.iter().position(|x| x == attribute) {
+            Some(i) => self.values[i].clone(),
+            None => self.source.lookup(attribute),
+        }
+    }
+
+    fn aggregate(&self, func: &Expr) -> Value {
+        self.source.aggregate(func)
+    }
+}
+
+/// A WHERE clause, split into the conditions joined by a top level `and` so
+/// tables can pull out the ones they need before scanning.
+pub struct Filter {
+    pub conjuncts: Vec<Expr>,
+}
+
+impl Filter {
+    pub fn new(expr: Option<&Expr>) -> Filter {
+        let mut conjuncts = Vec::new();
+        if let Some(e) = expr {
+            Filter::split(e.clone(), &mut conjuncts);
+        }
+        Filter { conjuncts }
+    }
+
+    fn split(expr: Expr, conjuncts: &mut Vec<Expr>) {
+        match expr {
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::And,
+                right,
+            } => {
+                Filter::split(*left, conjuncts);
+                Filter::split(*right, conjuncts);
+            }
+            e => conjuncts.push(e),
+        }
+    }
+
+    pub fn check<T: HasLookup>(&self, row: &T) -> bool {
+        self.conjuncts.iter().all(|x| eval(x, row) == Some(true))
+    }
+}
+
+/// Evaluates a WHERE expression against a row. `None` is SQL's unknown,
+/// which is what any comparison against NULL gives.
+pub fn eval<T: HasLookup>(expr: &Expr, row: &T) -> Option<bool> {
+    match expr {
+        Expr::Not(e) => eval(e, row).map(|x| !x),
+        Expr::IsNull { expr, negated } => Some(value_of(expr, row).is_null() != *negated),
+        Expr::InList {
+            expr,
+            list,
+            negated,
+        } => {
+            let v = value_of(expr, row);
+            let mut found = Some(false);
+            for x in list {
+                match compare(&v, BinaryOperator::Eq, &value_of(x, row)) {
+                    Some(true) => {
+                        found = Some(true);
+                        break;
+                    }
+                    None => found = None,
+                    _ => (),
+                }
+            }
+            found.map(|x| x != *negated)
+        }
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => match (eval(left, row), eval(right, row)) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        },
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Or,
+            right,
+        } => match (eval(left, row), eval(right, row)) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        },
+        Expr::BinaryOp { left, op, right } => {
+            compare(&value_of(left, row), *op, &value_of(right, row))
+        }
+        Expr::Column(_) | Expr::Literal(_) | Expr::Function { .. } => match value_of(expr, row) {
+            Value::Null => None,
+            Value::Bool(b) => Some(b),
+            v => Some(v.compare(&Value::Integer(0)) != Some(Ordering::Equal)),
+        },
+    }
+}
+
+pub fn value_of<T: HasLookup>(expr: &Expr, row: &T) -> Value {
+    match expr {
+        Expr::Column(c) => row.lookup(c),
+        Expr::Literal(l) => Value::from(l.clone()),
+        Expr::Function { .. } => row.aggregate(expr),
+        e => eval(e, row).into(),
+    }
+}
+
+fn compare(left: &Value, op: BinaryOperator, right: &Value) -> Option<bool> {
+    if op == BinaryOperator::Like {
+        return left.like(right);
+    }
+    let ord = left.compare(right)?;
+    Some(match op {
+        BinaryOperator::Eq => ord == Ordering::Equal,
+        BinaryOperator::NotEq => ord != Ordering::Equal,
+        BinaryOperator::Lt => ord == Ordering::Less,
+        BinaryOperator::LtEq => ord != Ordering::Greater,
+        BinaryOperator::Gt => ord == Ordering::Greater,
+        BinaryOperator::GtEq => ord != Ordering::Less,
+        _ => false,
+    })
+}
+
+/// Sorts rows by the ORDER BY columns, NULLs first.
+fn sort_rows<T: HasLookup>(rows: &mut [T], order_by: &[OrderByExpr]) {
+    if order_by.is_empty() {
+        return;
+    }
+    rows.sort_by(|a, b| {
+        order_by
+            .iter()
+            .map(|o| {
+                let ord = value_of(&o.expr, a).sort_cmp(&value_of(&o.expr, b));
+                if o.asc {
+                    ord
+                } else {
+                    ord.reverse()
+                }
+            })
+            .find(|x| *x != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+fn order_and_limit<T: HasLookup>(rows: &mut Vec<T>, stmt: &Statement) {
+    sort_rows(rows, &stmt.order_by);
+    if let Some(n) = stmt.offset {
+        let n = usize::try_from(n).unwrap_or(usize::MAX);
+        rows.drain(..rows.len().min(n));
+    }
+    if let Some(n) = stmt.limit {
+        rows.truncate(usize::try_from(n).unwrap_or(usize::MAX));
+    }
+}
+
+/// Checks the parts of a query that don't depend on the table.
+pub fn validate(stmt: &Statement) -> Result<(), QueryError> {
+    if let Some(e) = &stmt.selection {
+        check_functions(e, false)?;
+    }
+    for x in &stmt.projection {
+        match x {
+            SelectItem::Wildcard if stmt.is_aggregate() => {
+                return Err(QueryError::invalid(
+                    "* can't be used together with group by or aggregates".to_string(),
+                    "*",
+                ))
+            }
+            SelectItem::Expr { expr, .. } => check_functions(expr, true)?,
+            _ => (),
+        }
+    }
+    for e in stmt.group_by.iter() {
+        check_functions(e, false)?;
+    }
+    if let Some(e) = &stmt.having {
+        check_functions(e, true)?;
+    }
+    for o in stmt.order_by.iter() {
+        check_functions(&o.expr, stmt.is_aggregate())?;
+    }
+    Ok(())
+}
+
+/// A column of a query's result. Plain table columns know their table and
+/// type, and so do expressions whose type follows from what they are, like
+/// `count(*)` or `max(pid)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResultColumn {
+    /// The header, the alias if there is one, otherwise the expression.
+    pub name: String,
+    pub kind: Option<ColumnType>,
+    /// The table the column comes from, by its name rather than its alias.
+    pub table: Option<String>,
+}
+
+/// What a query returns. Every row holds one value per column, in the same
+/// order as `columns`. `warnings` lists what the tables couldn't read.
+pub struct ResultSet {
+    pub columns: Vec<ResultColumn>,
+    pub rows: Vec<Vec<Value>>,
+    pub warnings: Vec<Warning>,
+}
+
+impl ResultSet {
+    /// The column headers.
+    pub fn names(&self) -> Vec<&str> {
+        self.columns.iter().map(|x| x.name.as_str()).collect()
+    }
+}
+
+/// Looks up a column by the name a query uses for it, giving the name of its
+/// table and its type.
+pub type Resolve<'a> = dyn Fn(&str) -> Option<(String, ColumnType)> + 'a;
+
+/// The type of a select expression, if it can be known without running it.
+fn expr_type(expr: &Expr, resolve: &Resolve) -> Option<ColumnType> {
+    match expr {
+        Expr::Column(c) => resolve(c).map(|x| x.1),
+        Expr::Literal(Literal::Integer(_)) => Some(ColumnType::Integer),
+        Expr::Literal(Literal::String(_)) => Some(ColumnType::Text),
+        Expr::Literal(Literal::Bool(_)) => Some(ColumnType::Bool),
+        Expr::Function { name, args, .. } => match name.as_str() {
+            "count" => Some(ColumnType::Integer),
+            "group_concat" => Some(ColumnType::Text),
+            "min" | "max" => expr_type(args.first()?, resolve),
+            "sum" => match expr_type(args.first()?, resolve) {
+                Some(ColumnType::Integer) => Some(ColumnType::Integer),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The select list as result columns and their expressions, with `*`
+/// expanded to the `wildcard` columns.
+fn select_list(
+    stmt: &Statement,
+    wildcard: &[String],
+    resolve: &Resolve,
+) -> Vec<(ResultColumn, Expr)> {
+    let column = |name: String, expr: &Expr| ResultColumn {
+        name,
+        kind: expr_type(expr, resolve),
+        table: match expr {
+            Expr::Column(c) => resolve(c).map(|x| x.0),
+            _ => None,
+        },
+    };
+    let mut out = Vec::new();
+    for x in &stmt.projection {
+        match x {
+            SelectItem::Expr { expr, alias } => {
+                let name = alias.clone().unwrap_or_else(|| expr.to_string());
+                out.push((column(name, expr), expr.clone()));
+            }
+            SelectItem::Wildcard => {
+                for c in wildcard {
+                    let expr = Expr::Column(c.clone());
+                    out.push((column(c.clone(), &expr), expr));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Runs the select list over the filtered rows of a table, grouping them
+/// first if the query aggregates. `*` selects the `wildcard` columns, and
+/// `resolve` says where the columns come from.
+pub fn project<T: HasLookup>(
+    rows: &[T],
+    stmt: &Statement,
+    wildcard: &[String],
+    resolve: &Resolve,
+) -> ResultSet {
+    let select = select_list(stmt, wildcard, resolve);
+    let names: Vec<String> = select.iter().map(|x| x.0.name.clone()).collect();
+
+    let rows = if stmt.is_aggregate() {
+        let mut out: Vec<ProjectedRow<Group<T>>> = group_rows(rows, &stmt.group_by)
+            .into_iter()
+            .map(|g| ProjectedRow {
+                names: names.clone(),
+                values: select.iter().map(|(_, e)| value_of(e, &g)).collect(),
+                source: g,
+            })
+            .filter(|x| match &stmt.having {
+                Some(h) => eval(h, x) == Some(true),
+                None => true,
+            })
+            .collect();
+        order_and_limit(&mut out, stmt);
+        out.into_iter().map(|x| x.values).collect()
+    } else {
+        let mut out: Vec<ProjectedRow<&T>> = rows
+            .iter()
+            .map(|r| ProjectedRow {
+                names: names.clone(),
+                values: select.iter().map(|(_, e)| value_of(e, r)).collect(),
+                source: r,
+            })
+            .collect();
+        order_and_limit(&mut out, stmt);
+        out.into_iter().map(|x| x.values).collect()
+    };
+    ResultSet {
+        columns: select.into_iter().map(|x| x.0).collect(),
+        rows,
+        warnings: Vec::new(),
+    }
+}
+
+/// Reads the rows of a table that pass the where clause, stopping once
+/// `budget` of them are found. Anything the table couldn't read is added to
+/// `warnings`.
+pub fn scan(
+    table: &dyn Table,
+    mut filters: Filter,
+    budget: Option<usize>,
+    roots: &Roots,
+    warnings: &mut Vec<Warning>,
+) -> Result<Vec<Vec<Value>>, QueryError> {
+    let constraints = push_down(table, &mut filters)?;
+    let mut context = Context::new(table, constraints, filters, budget, roots.clone());
+    table.generate(&mut context)?;
+    let (rows, w) = context.finish();
+    warnings.extend(w);
+    Ok(rows)
+}
+
+/// Checks that comparisons between a column and a value make sense for the
+/// column's type, so `pid = 'abc'` is an error rather than no rows.
+pub fn check_types(
+    expr: &Expr,
+    column_type: &dyn Fn(&str) -> Option<ColumnType>,
+) -> Result<(), QueryError> {
+    let check = |column: &Expr, value: &Expr| match (column, value) {
+        (Expr::Column(c), Expr::Literal(l)) => match column_type(c) {
+            Some(t) if !t.accepts(&Value::from(l.clone())) => Err(QueryError::TypeMismatch {
+                column: c.clone(),
+                expected: t,
+                value: l.to_string(),
+            }),
+            _ => Ok(()),
+        },
+        _ => Ok(()),
+    };
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And | BinaryOperator::Or,
+            right,
+        } => {
+            check_types(left, column_type)?;
+            check_types(right, column_type)
+        }
+        Expr::BinaryOp {
+            op: BinaryOperator::Like,
+            ..
+        } => Ok(()),
+        Expr::BinaryOp { left, right, .. } => {
+            check(left, right)?;
+            check(right, left)
+        }
+        Expr::InList { expr, list, .. } => list.iter().try_for_each(|x| check(expr, x)),
+        Expr::Not(e) => check_types(e, column_type),
+        _ => Ok(()),
+    }
+}
+
+fn run(stmt: &Statement, roots: &Roots) -> Result<ResultSet, QueryError> {
+    if stmt.has_joins() {
+        return join::query_join(stmt, roots);
+    }
+    validate(stmt)?;
+    let table = tables::find(&stmt.from.name).ok_or_else(|| QueryError::UnknownTable {
+        name: stmt.from.name.clone(),
+    })?;
+    let aliases = stmt.aliases();
+    let columns = stmt.source_columns().into_iter().map(|c| (c, false));
+    for (c, output) in columns.chain(stmt.output_columns().into_iter().map(|c| (c, true))) {
+        let known = table.has_column(c) || (output && aliases.contains(&c));
+        if !known {
+            return Err(QueryError::UnknownColumn {
+                name: c.to_string(),
+                table: Some(table.name().to_string()),
+            });
+        }
+    }
+    if let Some(e) = &stmt.selection {
+        check_types(e, &|c| table.column_type(c))?;
+    }
+    let filters = Filter::new(stmt.selection.as_ref());
+    let mut warnings = Vec::new();
+    let values = scan(table, filters, stmt.row_budget(), roots, &mut warnings)?;
+    let columns = table.columns();
+    let rows: Vec<Row> = values.iter().map(|v| Row { columns, values: v }).collect();
+    let resolve = |c: &str| Some((table.name().to_string(), table.column_type(c)?));
+    let mut result = project(&rows, stmt, &table.visible_columns(), &resolve);
+    result.warnings = warnings;
+    Ok(result)
+}
+
+/// Runs queries against the tables of the machine it's running on, or of
+/// the directory tree set with `with_root`.
+#[derive(Default)]
+pub struct Engine {
+    roots: Roots,
+}
+
+impl Engine {
+    pub fn new() -> Engine {
+        Engine::default()
+    }
+
+    /// Reads files and procfs from under `root` instead of `/`, e.g. a
+    /// mounted disk image or a container's `/proc/<pid>/root`.
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Engine {
+        self.roots.set_root(root);
+        self
+    }
+
+    /// Reads procfs from `proc` instead of `<root>/proc`.
+    pub fn with_proc_root(mut self, proc: impl Into<PathBuf>) -> Engine {
+        self.roots.set_proc(proc);
+        self
+    }
+
+    pub fn roots(&self) -> &Roots {
+        &self.roots
+    }
+
+    /// Parses and runs a query.
+    pub fn query(&self, sql: &str) -> Result<ResultSet, QueryError> {
+        let stmt = parse_statement(sql).map_err(|e| QueryError::parse(e, sql))?;
+        self.execute(&stmt)
+    }
+
+    /// Runs a query that has already been parsed.
+    pub fn execute(&self, stmt: &Statement) -> Result<ResultSet, QueryError> {
+        run(stmt, &self.roots)
+    }
+
+    /// Every table that can be queried.
+    pub fn tables(&self) -> &'static [&'static dyn Table] {
+        tables::all()
+    }
+
+    pub fn table(&self, name: &str) -> Option<&'static dyn Table> {
+        tables::find(name)
+    }
+}
@@ -0,0 +1,178 @@
+use super::sql::ParseError;
+use super::tables::ColumnType;
+use regex::Regex;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Why a query failed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum QueryError {
+    /// The query isn't valid SQL. `span` is the byte range it failed at.
+    Parse {
+        message: String,
+        span: Range<usize>,
+    },
+    UnknownTable {
+        name: String,
+    },
+    /// A column no table in the query has. `table` is set when the column
+    /// was looked up in one particular table.
+    UnknownColumn {
+        name: String,
+        table: Option<String>,
+    },
+    /// A table that can't be scanned without a constraint on `column`.
+    MissingConstraint {
+        table: String,
+        column: String,
+        operators: String,
+    },
+    PermissionDenied {
+        path: PathBuf,
+    },
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    /// A column compared with a value that can never match its type.
+    TypeMismatch {
+        column: String,
+        expected: ColumnType,
+        value: String,
+    },
+    /// Anything else wrong with the query, like an unknown function.
+    /// `token` is the part of the query to point at, if there is one.
+    Invalid {
+        message: String,
+        token: Option<String>,
+    },
+}
+
+impl QueryError {
+    /// A parse error in `sql`, spanning the word it failed at.
+    pub fn parse(e: ParseError, sql: &str) -> QueryError {
+        let start = e.position.min(sql.len());
+        let rest = &sql[start..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == ';')
+            .unwrap_or(rest.len());
+        QueryError::Parse {
+            message: e.message,
+            span: start..start + len.max(1),
+        }
+    }
+
+    pub fn invalid(message: String, token: &str) -> QueryError {
+        QueryError::Invalid {
+            message,
+            token: Some(token.to_string()),
+        }
+    }
+
+    /// An error from reading `path`.
+    pub fn io(path: &Path, e: io::Error) -> QueryError {
+        match e.kind() {
+            io::ErrorKind::PermissionDenied => QueryError::PermissionDenied {
+                path: path.to_path_buf(),
+            },
+            _ => QueryError::Io {
+                path: path.to_path_buf(),
+                message: e.to_string(),
+            },
+        }
+    }
+
+    /// The byte range of `sql` the error is about, if it can be pointed at.
+    pub fn span(&self, sql: &str) -> Option<Range<usize>> {
+        let token = match self {
+            QueryError::Parse { span, .. } => return Some(span.clone()),
+            QueryError::UnknownTable { name } => name,
+            QueryError::UnknownColumn { name, .. } => name,
+            QueryError::MissingConstraint { table, .. } => table,
+            QueryError::TypeMismatch { value, .. } => value,
+            QueryError::Invalid {
+                token: Some(token), ..
+            } => token,
+            _ => return None,
+        };
+        locate(sql, token)
+    }
+}
+
+/// Finds the first place `token` appears in `sql` as a whole word.
+fn locate(sql: &str, token: &str) -> Option<Range<usize>> {
+    let re = Regex::new(&format!(r"(?i)(^|[^\w.]){}($|[^\w])", regex::escape(token))).ok()?;
+    let m = re.captures(sql)?;
+    let start = m.get(1)?.end();
+    Some(start..start + token.len())
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Parse { message, span } => {
+                write!(f, "{} at position {}", message, span.start)
+            }
+            QueryError::UnknownTable { name } => write!(f, "Table {} does not exist", name),
+            QueryError::UnknownColumn {
+                name,
+                table: Some(table),
+            } => write!(f, "Unknown column {} in table {}", name, table),
+            QueryError::UnknownColumn { name, table: None } => {
+                write!(f, "Unknown column {}", name)
+            }
+            QueryError::MissingConstraint {
+                table,
+                column,
+                operators,
+            } => write!(
+                f,
+                "Table {} needs a constraint on {} using {}, e.g. where {} = ...",
+                table, column, operators, column
+            ),
+            QueryError::PermissionDenied { path } => {
+                write!(f, "Permission denied reading {}", path.display())
+            }
+            QueryError::Io { path, message } => {
+                write!(f, "Couldn't read {}: {}", path.display(), message)
+            }
+            QueryError::TypeMismatch {
+                column,
+                expected,
+                value,
+            } => write!(
+                f,
+                "Type mismatch: {} is {}, it can't be compared with {}",
+                column, expected, value
+            ),
+            QueryError::Invalid { message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Something a table couldn't read while generating rows. The query still
+/// runs, without (or with nulls in place of) whatever couldn't be read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl Warning {
+    pub fn new(path: &Path, reason: impl fmt::Display) -> Warning {
+        Warning {
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.reason)
+    }
+}
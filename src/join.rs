@@ -0,0 +1,339 @@
+use super::constraint;
+use super::engine::{self, eval, value_of, Filter, HasLookup, ResultSet};
+use super::error::{QueryError, Warning};
+use super::sql::{BinaryOperator, Expr, JoinKind, Statement};
+use super::tables::{self, Column, Roots, Table};
+use super::value::Value;
+use std::rc::Rc;
+
+/// One table's share of a joined row. `values` is `None` for the NULL row a
+/// left join adds when nothing matched.
+#[derive(Clone)]
+struct Part {
+    name: Rc<str>,
+    columns: &'static [Column],
+    values: Option<Rc<Vec<Value>>>,
+}
+
+/// A row made of one row from each table in the query. Columns are looked
+/// up as `table.column`, or as just `column` if only one table has it.
+#[derive(Clone)]
+pub struct JoinedRow {
+    parts: Vec<Part>,
+}
+
+impl HasLookup for JoinedRow {
+    fn lookup(&self, attribute: &str) -> Value {
+        let (table, column) = match attribute.split_once('.') {
+            Some((t, c)) => (Some(t), c),
+            None => (None, attribute),
+        };
+        for p in &self.parts {
+            if table.is_some_and(|t| t != &*p.name) {
+                continue;
+            }
+            if let Some(i) = p.columns.iter().position(|x| x.name == column) {
+                return match &p.values {
+                    Some(v) => v[i].clone(),
+                    None => Value::Null,
+                };
+            }
+        }
+        Value::Null
+    }
+}
+
+/// The tables in a query in join order, with the name each is referred to by.
+struct Tables {
+    names: Vec<Rc<str>>,
+    defs: Vec<&'static dyn Table>,
+}
+
+impl Tables {
+    /// Finds which table a column belongs to. `Ok(None)` means no table has
+    /// it, which is only fine for select aliases.
+    fn resolve(&self, column: &str) -> Result<Option<usize>, QueryError> {
+        if let Some((t, c)) = column.split_once('.') {
+            return match self.names.iter().position(|x| &**x == t) {
+                Some(i) if self.defs[i].has_column(c) => Ok(Some(i)),
+                Some(i) => Err(QueryError::UnknownColumn {
+                    name: column.to_string(),
+                    table: Some(self.defs[i].name().to_string()),
+                }),
+                None => Err(QueryError::invalid(
+                    format!("Unknown table {} in {}", t, column),
+                    column,
+                )),
+            };
+        }
+        let found: Vec<usize> = (0..self.names.len())
+            .filter(|i| self.defs[*i].has_column(column))
+            .collect();
+        match found.len() {
+            0 => Ok(None),
+            1 => Ok(Some(found[0])),
+            _ => Err(QueryError::invalid(
+                format!("Ambiguous column name {}", column),
+                column,
+            )),
+        }
+    }
+
+    /// True if every column in the expression belongs to the tables in `range`.
+    fn only_uses(&self, expr: &Expr, range: std::ops::Range<usize>) -> bool {
+        let mut cols = Vec::new();
+        expr.collect_columns(&mut cols);
+        cols.iter()
+            .all(|c| matches!(self.resolve(c), Ok(Some(i)) if range.contains(&i)))
+    }
+}
+
+fn strip_qualifiers(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Column(c) => match c.split_once('.') {
+            Some((_, c)) => Expr::Column(c.to_string()),
+            None => Expr::Column(c.clone()),
+        },
+        Expr::Literal(_) => expr.clone(),
+        Expr::BinaryOp { left, op, right } => Expr::BinaryOp {
+            left: Box::new(strip_qualifiers(left)),
+            op: *op,
+            right: Box::new(strip_qualifiers(right)),
+        },
+        Expr::Not(e) => Expr::Not(Box::new(strip_qualifiers(e))),
+        Expr::IsNull { expr, negated } => Expr::IsNull {
+            expr: Box::new(strip_qualifiers(expr)),
+            negated: *negated,
+        },
+        Expr::InList {
+            expr,
+            list,
+            negated,
+        } => Expr::InList {
+            expr: Box::new(strip_qualifiers(expr)),
+            list: list.iter().map(strip_qualifiers).collect(),
+            negated: *negated,
+        },
+        Expr::Function {
+            name,
+            distinct,
+            args,
+        } => Expr::Function {
+            name: name.clone(),
+            distinct: *distinct,
+            args: args.iter().map(strip_qualifiers).collect(),
+        },
+    }
+}
+
+/// The conditions that only read table `i`, rewritten so the table's own
+/// scan can check them.
+fn local_conditions(conjuncts: &[Expr], tables: &Tables, i: usize) -> Vec<Expr> {
+    conjuncts
+        .iter()
+        .filter(|x| tables.only_uses(x, i..i + 1))
+        .map(strip_qualifiers)
+        .collect()
+}
+
+/// Looks for `table.column = <expr>` in a join condition where the other
+/// side only reads tables joined earlier, and returns that side.
+fn find_binding<'a>(on: &'a [Expr], tables: &Tables, i: usize, column: &str) -> Option<&'a Expr> {
+    on.iter().find_map(|x| match x {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Eq,
+            right,
+        } => {
+            let binds = |side: &Expr| match side {
+                Expr::Column(c) => {
+                    let bare = c.split_once('.').map_or(c.as_str(), |x| x.1);
+                    bare == column && matches!(tables.resolve(c), Ok(Some(t)) if t == i)
+                }
+                _ => false,
+            };
+            if binds(left) && tables.only_uses(right, 0..i) {
+                Some(right.as_ref())
+            } else if binds(right) && tables.only_uses(left, 0..i) {
+                Some(left.as_ref())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    })
+}
+
+fn scan(
+    table: &dyn Table,
+    conjuncts: Vec<Expr>,
+    roots: &Roots,
+    warnings: &mut Vec<Warning>,
+) -> Result<Vec<Rc<Vec<Value>>>, QueryError> {
+    let rows = engine::scan(table, Filter { conjuncts }, None, roots, warnings)?;
+    Ok(rows.into_iter().map(Rc::new).collect())
+}
+
+/// Runs a query over several tables with nested loop joins, left to right.
+/// Tables that need a column value to be scanned at all (like `proc_maps`
+/// needing a `pid`) are scanned once per row joined so far, with the value
+/// taken from the `on` condition.
+pub fn query_join(stmt: &Statement, roots: &Roots) -> Result<ResultSet, QueryError> {
+    engine::validate(stmt)?;
+
+    let refs: Vec<_> = std::iter::once(&stmt.from)
+        .chain(stmt.joins.iter().map(|j| &j.table))
+        .collect();
+    let mut tables = Tables {
+        names: Vec::new(),
+        defs: Vec::new(),
+    };
+    for t in &refs {
+        let table = tables::find(&t.name).ok_or_else(|| QueryError::UnknownTable {
+            name: t.name.clone(),
+        })?;
+        if tables.names.iter().any(|x| &**x == t.reference()) {
+            return Err(QueryError::invalid(
+                format!("Table {} is used twice, give it an alias", t.reference()),
+                t.reference(),
+            ));
+        }
+        tables.names.push(t.reference().into());
+        tables.defs.push(table);
+    }
+    let aliases = stmt.aliases();
+    let columns = stmt.source_columns().into_iter().map(|c| (c, false));
+    for (c, output) in columns.chain(stmt.output_columns().into_iter().map(|c| (c, true))) {
+        let known = tables.resolve(c)?.is_some() || (output && aliases.contains(&c));
+        if !known {
+            return Err(QueryError::UnknownColumn {
+                name: c.to_string(),
+                table: None,
+            });
+        }
+    }
+
+    let column_type = |c: &str| {
+        let i = tables.resolve(c).ok()??;
+        tables.defs[i].column_type(c.rsplit('.').next()?)
+    };
+    for e in stmt
+        .selection
+        .iter()
+        .chain(stmt.joins.iter().map(|j| &j.on))
+    {
+        engine::check_types(e, &column_type)?;
+    }
+
+    let where_conjuncts = Filter::new(stmt.selection.as_ref()).conjuncts;
+    let mut warnings = Vec::new();
+    let part = |i: usize, values: Option<Rc<Vec<Value>>>| Part {
+        name: tables.names[i].clone(),
+        columns: tables.defs[i].columns(),
+        values,
+    };
+
+    let mut rows: Vec<JoinedRow> = scan(
+        tables.defs[0],
+        local_conditions(&where_conjuncts, &tables, 0),
+        roots,
+        &mut warnings,
+    )?
+    .into_iter()
+    .map(|v| JoinedRow {
+        parts: vec![part(0, Some(v))],
+    })
+    .collect();
+
+    for (n, join) in stmt.joins.iter().enumerate() {
+        let i = n + 1;
+        let on = Filter::new(Some(&join.on)).conjuncts;
+        let mut local = local_conditions(&on, &tables, i);
+        if join.kind == JoinKind::Inner {
+            local.extend(local_conditions(&where_conjuncts, &tables, i));
+        }
+
+        // Scan once up front unless the table needs values from each row.
+        let mut bindings = Vec::new();
+        for c in tables.defs[i].index_columns() {
+            if !c.required || constraint::is_constrained(&local, c) {
+                continue;
+            }
+            let b = find_binding(&on, &tables, i, c.name).ok_or_else(|| {
+                QueryError::invalid(
+                    format!(
+                        "Table {} needs {}.{} = <value> in its join condition",
+                        join.table.name, tables.names[i], c.name
+                    ),
+                    &join.table.name,
+                )
+            })?;
+            bindings.push((c.name, b));
+        }
+        let shared = if bindings.is_empty() {
+            Some(scan(tables.defs[i], local.clone(), roots, &mut warnings)?)
+        } else {
+            None
+        };
+
+        let mut out = Vec::new();
+        for mut left in rows {
+            let right = match &shared {
+                Some(r) => r.clone(),
+                None => {
+                    let mut conjuncts = local.clone();
+                    for (c, b) in &bindings {
+                        conjuncts.push(Expr::BinaryOp {
+                            left: Box::new(Expr::Column(c.to_string())),
+                            op: BinaryOperator::Eq,
+                            right: Box::new(Expr::Literal(value_of(b, &left).into())),
+                        });
+                    }
+                    scan(tables.defs[i], conjuncts, roots, &mut warnings)?
+                }
+            };
+
+            let mut matched = false;
+            for r in right {
+                let mut joined = left.clone();
+                joined.parts.push(part(i, Some(r)));
+                if on.iter().all(|x| eval(x, &joined) == Some(true)) {
+                    out.push(joined);
+                    matched = true;
+                }
+            }
+            if !matched && join.kind == JoinKind::Left {
+                left.parts.push(part(i, None));
+                out.push(left);
+            }
+        }
+        rows = out;
+    }
+
+    let filters = Filter {
+        conjuncts: where_conjuncts,
+    };
+    rows.retain(|x| filters.check(x));
+
+    let wildcard: Vec<String> = tables
+        .names
+        .iter()
+        .zip(tables.defs.iter())
+        .flat_map(|(n, t)| {
+            t.visible_columns()
+                .into_iter()
+                .map(move |c| format!("{}.{}", n, c))
+        })
+        .filter(|x| !aliases.contains(&x.as_str()))
+        .collect();
+    let resolve = |c: &str| {
+        let table = tables.defs[tables.resolve(c).ok()??];
+        Some((
+            table.name().to_string(),
+            table.column_type(c.rsplit('.').next()?)?,
+        ))
+    };
+    let mut result = engine::project(&rows, stmt, &wildcard, &resolve);
+    result.warnings = warnings;
+    Ok(result)
+}
@@ -0,0 +1,22 @@
+//! Query the machine you're running on with SQL.
+//!
+//! ```no_run
+//! let engine = rusty_osquery::Engine::new();
+//! let result = engine.query("select pid, cmdline from procs limit 5").unwrap();
+//! for row in &result.rows {
+//!     println!("{} {}", row[0], row[1]);
+//! }
+//! ```
+
+mod aggregate;
+pub mod constraint;
+pub mod engine;
+mod error;
+mod join;
+pub mod sql;
+pub mod tables;
+pub mod value;
+
+pub use self::engine::{Engine, ResultColumn, ResultSet};
+pub use self::error::{QueryError, Warning};
+pub use self::value::Value;
@@ -6,5 +6,5 @@ fn main() {
     if env::consts::OS != "linux" {
         panic!("[CRITICAL HIT] Oh Nos! We don't support {} yet!", env::consts::OS);
     }
-    app::mainloop();
+    std::process::exit(app::run());
 }
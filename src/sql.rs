@@ -0,0 +1,1076 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, tag_no_case},
+    character::complete::{
+        alpha1, alphanumeric1, char, digit1, hex_digit1, multispace0, multispace1, one_of,
+    },
+    combinator::{all_consuming, cut, map, map_res, not, opt, peek, recognize, success, value},
+    error::{context, ErrorKind, ParseError as _, VerboseError, VerboseErrorKind},
+    multi::{many0, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+use std::fmt;
+
+type Res<'a, T> = IResult<&'a str, T, VerboseError<&'a str>>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statement {
+    pub projection: Vec<SelectItem>,
+    pub from: TableRef,
+    pub joins: Vec<Join>,
+    pub selection: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    pub having: Option<Expr>,
+    pub order_by: Vec<OrderByExpr>,
+    pub limit: Option<u64>,
+    pub offset: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableRef {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JoinKind {
+    Inner,
+    Left,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Join {
+    pub kind: JoinKind,
+    pub table: TableRef,
+    pub on: Expr,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectItem {
+    Wildcard,
+    Expr { expr: Expr, alias: Option<String> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderByExpr {
+    pub expr: Expr,
+    pub asc: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Integer(i64),
+    Real(f64),
+    String(String),
+    Bool(bool),
+    Blob(Vec<u8>),
+    Null,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOperator {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Like,
+    And,
+    Or,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// A column, `name` or `table.name` when qualified with a table or alias.
+    Column(String),
+    Literal(Literal),
+    BinaryOp {
+        left: Box<Expr>,
+        op: BinaryOperator,
+        right: Box<Expr>,
+    },
+    Not(Box<Expr>),
+    IsNull {
+        expr: Box<Expr>,
+        negated: bool,
+    },
+    /// `expr in (a, b, c)`, or `not in` when negated.
+    InList {
+        expr: Box<Expr>,
+        list: Vec<Expr>,
+        negated: bool,
+    },
+    /// A function call, `count(*)` is written with no arguments.
+    Function {
+        name: String,
+        distinct: bool,
+        args: Vec<Expr>,
+    },
+}
+
+pub const AGGREGATES: [&str; 6] = ["count", "sum", "min", "max", "avg", "group_concat"];
+
+/// A query that could not be parsed, with the byte offset it failed at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl Statement {
+    /// True if the query reads more than one table, or names its table with
+    /// an alias or in front of a column, so columns need resolving by table.
+    pub fn has_joins(&self) -> bool {
+        !self.joins.is_empty()
+            || self.from.alias.is_some()
+            || self.columns_referenced().iter().any(|x| x.contains('.'))
+    }
+
+    /// Every column named anywhere in the query, including select aliases
+    /// used by later clauses.
+    pub fn columns_referenced(&self) -> Vec<&str> {
+        let mut out = self.source_columns();
+        out.extend(self.output_columns());
+        out
+    }
+
+    /// Columns that have to come from the tables: those in the select list,
+    /// `on`, `where` and `group by`, which all run before there are any
+    /// select aliases to use.
+    pub fn source_columns(&self) -> Vec<&str> {
+        let mut out = Vec::new();
+        for x in &self.projection {
+            if let SelectItem::Expr { expr, .. } = x {
+                expr.collect_columns(&mut out);
+            }
+        }
+        for j in &self.joins {
+            j.on.collect_columns(&mut out);
+        }
+        for e in self.selection.iter().chain(self.group_by.iter()) {
+            e.collect_columns(&mut out);
+        }
+        out
+    }
+
+    /// Columns in `having` and `order by`, which run on the selected rows and
+    /// so can also name a select alias.
+    pub fn output_columns(&self) -> Vec<&str> {
+        let mut out = Vec::new();
+        if let Some(e) = &self.having {
+            e.collect_columns(&mut out);
+        }
+        for o in &self.order_by {
+            o.expr.collect_columns(&mut out);
+        }
+        out
+    }
+
+    /// Names given to select items with `as`.
+    pub fn aliases(&self) -> Vec<&str> {
+        self.projection
+            .iter()
+            .filter_map(|x| match x {
+                SelectItem::Expr { alias: Some(a), .. } => Some(a.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// True if the query collapses rows, either with `group by` or by using
+    /// an aggregate function in the select list, `having` or `order by`.
+    pub fn is_aggregate(&self) -> bool {
+        !self.group_by.is_empty()
+            || self.having.is_some()
+            || self.projection.iter().any(|x| match x {
+                SelectItem::Expr { expr, .. } => expr.has_aggregate(),
+                _ => false,
+            })
+            || self.order_by.iter().any(|x| x.expr.has_aggregate())
+    }
+
+    /// How many matching rows a table has to produce before it can stop,
+    /// `None` if it has to produce all of them (no limit, or they get sorted
+    /// or grouped).
+    pub fn row_budget(&self) -> Option<usize> {
+        if !self.order_by.is_empty() || self.is_aggregate() {
+            return None;
+        }
+        self.limit.map(|n| {
+            let n = n.saturating_add(self.offset.unwrap_or(0));
+            usize::try_from(n).unwrap_or(usize::MAX)
+        })
+    }
+}
+
+impl TableRef {
+    /// The name columns of this table are qualified with.
+    pub fn reference(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl Expr {
+    pub fn collect_columns<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Column(c) => out.push(c),
+            Expr::Literal(_) => (),
+            Expr::BinaryOp { left, right, .. } => {
+                left.collect_columns(out);
+                right.collect_columns(out);
+            }
+            Expr::Not(e) | Expr::IsNull { expr: e, .. } => e.collect_columns(out),
+            Expr::InList { expr, list, .. } => {
+                expr.collect_columns(out);
+                list.iter().for_each(|x| x.collect_columns(out));
+            }
+            Expr::Function { args, .. } => args.iter().for_each(|x| x.collect_columns(out)),
+        }
+    }
+
+    pub fn is_aggregate(&self) -> bool {
+        matches!(self, Expr::Function { name, .. } if AGGREGATES.contains(&name.as_str()))
+    }
+
+    pub fn has_aggregate(&self) -> bool {
+        match self {
+            Expr::Function { args, .. } => {
+                self.is_aggregate() || args.iter().any(|x| x.has_aggregate())
+            }
+            Expr::BinaryOp { left, right, .. } => left.has_aggregate() || right.has_aggregate(),
+            Expr::Not(e) | Expr::IsNull { expr: e, .. } => e.has_aggregate(),
+            Expr::InList { expr, list, .. } => {
+                expr.has_aggregate() || list.iter().any(|x| x.has_aggregate())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Column(c) => write!(f, "{}", c),
+            Expr::Literal(Literal::String(s)) => write!(f, "\"{}\"", s),
+            Expr::Literal(Literal::Blob(b)) => write!(f, "x'{}'", hex::encode(b)),
+            Expr::Literal(l) => write!(f, "{}", l),
+            Expr::BinaryOp { left, op, right } => write!(f, "{} {} {}", left, op, right),
+            Expr::Not(e) => write!(f, "not {}", e),
+            Expr::IsNull { expr, negated } => {
+                write!(f, "{} is {}null", expr, if *negated { "not " } else { "" })
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let list = list
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let not = if *negated { "not " } else { "" };
+                write!(f, "{} {}in ({})", expr, not, list)
+            }
+            Expr::Function {
+                name,
+                distinct,
+                args,
+            } => {
+                let args = if args.is_empty() {
+                    "*".to_string()
+                } else {
+                    args.iter()
+                        .map(|x| x.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                };
+                let distinct = if *distinct { "distinct " } else { "" };
+                write!(f, "{}({}{})", name, distinct, args)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Integer(i) => write!(f, "{}", i),
+            Literal::Real(r) => write!(f, "{}", r),
+            Literal::String(s) => write!(f, "{}", s),
+            Literal::Bool(b) => write!(f, "{}", b),
+            Literal::Blob(b) => write!(f, "{}", hex::encode(b)),
+            Literal::Null => write!(f, "null"),
+        }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryOperator::Eq => write!(f, "="),
+            BinaryOperator::NotEq => write!(f, "!="),
+            BinaryOperator::Lt => write!(f, "<"),
+            BinaryOperator::LtEq => write!(f, "<="),
+            BinaryOperator::Gt => write!(f, ">"),
+            BinaryOperator::GtEq => write!(f, ">="),
+            BinaryOperator::Like => write!(f, "like"),
+            BinaryOperator::And => write!(f, "and"),
+            BinaryOperator::Or => write!(f, "or"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+/// Keywords can't be used as bare column or table names.
+pub const KEYWORDS: [&str; 27] = [
+    "select", "from", "where", "group", "having", "order", "by", "asc", "desc", "limit", "offset",
+    "like", "and", "or", "not", "is", "null", "true", "false", "as", "distinct", "join", "inner",
+    "left", "outer", "on", "in",
+];
+
+fn keyword<'a>(kw: &'static str) -> impl FnMut(&'a str) -> Res<'a, &'a str> {
+    terminated(tag_no_case(kw), not(peek(alt((alphanumeric1, tag("_"))))))
+}
+
+pub fn identifier(input: &str) -> Res<'_, &str> {
+    // [a-zA-Z_][a-zA-Z0-9_]*
+    let (rest, m) = recognize(pair(
+        alt((alpha1, tag("_"))),
+        many0(alt((alphanumeric1, tag("_")))),
+    ))(input)?;
+    if KEYWORDS.contains(&m.to_lowercase().as_str()) {
+        return Err(nom::Err::Error(VerboseError::from_error_kind(
+            input,
+            ErrorKind::Tag,
+        )));
+    }
+    Ok((rest, m))
+}
+
+fn string_literal(input: &str) -> Res<'_, String> {
+    let (rest, s) = alt((
+        delimited(char('"'), opt(is_not("\"")), char('"')),
+        delimited(char('\''), opt(is_not("'")), char('\'')),
+    ))(input)?;
+    Ok((rest, s.unwrap_or("").to_string()))
+}
+
+fn number_literal(input: &str) -> Res<'_, Literal> {
+    map_res(
+        recognize(tuple((
+            opt(char('-')),
+            digit1,
+            opt(pair(char('.'), digit1)),
+        ))),
+        |x: &str| {
+            if x.contains('.') {
+                x.parse::<f64>().map(Literal::Real).map_err(|_| ())
+            } else {
+                x.parse::<i64>().map(Literal::Integer).map_err(|_| ())
+            }
+        },
+    )(input)
+}
+
+/// sqlite style blob literal, `x'7f454c46'`.
+fn blob_literal(input: &str) -> Res<'_, Vec<u8>> {
+    map_res(
+        preceded(
+            one_of("xX"),
+            delimited(char('\''), recognize(many0(hex_digit1)), char('\'')),
+        ),
+        hex::decode,
+    )(input)
+}
+
+pub fn literal(input: &str) -> Res<'_, Literal> {
+    context(
+        "expected a quoted string, a number, true, false or null",
+        alt((
+            map(blob_literal, Literal::Blob),
+            map(string_literal, Literal::String),
+            number_literal,
+            value(Literal::Bool(true), keyword("true")),
+            value(Literal::Bool(false), keyword("false")),
+            value(Literal::Null, keyword("null")),
+        )),
+    )(input)
+}
+
+pub fn comparison_op(input: &str) -> Res<'_, BinaryOperator> {
+    context(
+        "expected a comparison operator",
+        alt((
+            value(BinaryOperator::LtEq, tag("<=")),
+            value(BinaryOperator::GtEq, tag(">=")),
+            value(BinaryOperator::NotEq, tag("!=")),
+            value(BinaryOperator::NotEq, tag("<>")),
+            value(BinaryOperator::Eq, tag("=")),
+            value(BinaryOperator::Lt, tag("<")),
+            value(BinaryOperator::Gt, tag(">")),
+            value(BinaryOperator::Like, keyword("like")),
+        )),
+    )(input)
+}
+
+fn is_null(input: &str) -> Res<'_, bool> {
+    let (rest, _) = tuple((multispace1, keyword("is"), multispace1))(input)?;
+    cut(context(
+        "expected null or not null",
+        terminated(
+            map(opt(terminated(keyword("not"), multispace1)), |x| {
+                x.is_some()
+            }),
+            keyword("null"),
+        ),
+    ))(rest)
+}
+
+fn in_list(input: &str) -> Res<'_, (bool, Vec<Expr>)> {
+    let (rest, negated) = delimited(
+        multispace1,
+        map(opt(terminated(keyword("not"), multispace1)), |x| {
+            x.is_some()
+        }),
+        keyword("in"),
+    )(input)?;
+    let (rest, list) = cut(delimited(
+        pair(multispace0, context("expected (", char('('))),
+        delimited(
+            multispace0,
+            separated_list1(delimited(multispace0, char(','), multispace0), operand),
+            multispace0,
+        ),
+        context("expected )", char(')')),
+    ))(rest)?;
+    Ok((rest, (negated, list)))
+}
+
+fn function_call(input: &str) -> Res<'_, Expr> {
+    let (rest, name) = terminated(identifier, pair(multispace0, char('(')))(input)?;
+    let (rest, distinct) = cut(preceded(
+        multispace0,
+        map(opt(terminated(keyword("distinct"), multispace1)), |x| {
+            x.is_some()
+        }),
+    ))(rest)?;
+    let (rest, args) = cut(terminated(
+        alt((
+            value(Vec::new(), char('*')),
+            separated_list0(delimited(multispace0, char(','), multispace0), operand),
+        )),
+        context("expected )", pair(multispace0, char(')'))),
+    ))(rest)?;
+    Ok((
+        rest,
+        Expr::Function {
+            name: name.to_lowercase(),
+            distinct,
+            args,
+        },
+    ))
+}
+
+/// A column name, optionally qualified with a table, `p.pid`.
+fn column_ref(input: &str) -> Res<'_, &str> {
+    recognize(pair(identifier, opt(pair(char('.'), cut(identifier)))))(input)
+}
+
+/// A literal, a function call or a column.
+fn operand(input: &str) -> Res<'_, Expr> {
+    context(
+        "expected a column, a value or a function",
+        alt((
+            map(literal, Expr::Literal),
+            function_call,
+            map(column_ref, |x| Expr::Column(x.to_string())),
+        )),
+    )(input)
+}
+
+fn comparison(input: &str) -> Res<'_, Expr> {
+    let (rest, subject) = operand(input)?;
+    let subject = Box::new(subject);
+    match is_null(rest) {
+        Ok((rest, negated)) => {
+            return Ok((
+                rest,
+                Expr::IsNull {
+                    expr: subject,
+                    negated,
+                },
+            ))
+        }
+        Err(nom::Err::Failure(e)) => return Err(nom::Err::Failure(e)),
+        _ => (),
+    }
+    match in_list(rest) {
+        Ok((rest, (negated, list))) => {
+            return Ok((
+                rest,
+                Expr::InList {
+                    expr: subject,
+                    list,
+                    negated,
+                },
+            ))
+        }
+        Err(nom::Err::Failure(e)) => return Err(nom::Err::Failure(e)),
+        _ => (),
+    }
+    // Without an operator the value stands on its own, e.g. `where on_disk`.
+    let (rest, op) = match preceded(multispace0, comparison_op)(rest) {
+        Ok(x) => x,
+        Err(nom::Err::Error(_)) => return Ok((rest, *subject)),
+        Err(e) => return Err(e),
+    };
+    let (rest, target) = cut(preceded(multispace0, operand))(rest)?;
+    Ok((
+        rest,
+        Expr::BinaryOp {
+            left: subject,
+            op,
+            right: Box::new(target),
+        },
+    ))
+}
+
+fn and_sep(input: &str) -> Res<'_, ()> {
+    alt((
+        value((), delimited(multispace0, char(','), multispace0)),
+        value((), delimited(multispace0, keyword("and"), multispace0)),
+    ))(input)
+}
+
+fn or_sep(input: &str) -> Res<'_, ()> {
+    value((), delimited(multispace0, keyword("or"), multispace0))(input)
+}
+
+fn fold_binary(first: Expr, others: Vec<Expr>, op: BinaryOperator) -> Expr {
+    others.into_iter().fold(first, |acc, x| Expr::BinaryOp {
+        left: Box::new(acc),
+        op,
+        right: Box::new(x),
+    })
+}
+
+fn primary(input: &str) -> Res<'_, Expr> {
+    alt((
+        delimited(
+            pair(char('('), multispace0),
+            cut(condition),
+            cut(context("expected )", pair(multispace0, char(')')))),
+        ),
+        comparison,
+    ))(input)
+}
+
+fn negation(input: &str) -> Res<'_, Expr> {
+    alt((
+        map(
+            preceded(pair(keyword("not"), multispace0), cut(negation)),
+            |x| Expr::Not(Box::new(x)),
+        ),
+        primary,
+    ))(input)
+}
+
+fn conjunction(input: &str) -> Res<'_, Expr> {
+    let (rest, first) = negation(input)?;
+    let (rest, others) = many0(preceded(and_sep, cut(negation)))(rest)?;
+    Ok((rest, fold_binary(first, others, BinaryOperator::And)))
+}
+
+/// A boolean expression of comparisons, or of values that are true unless
+/// they're false, zero or NULL. `not` binds tightest, then `and`
+/// (or a comma, kept for older queries), then `or`.
+fn condition(input: &str) -> Res<'_, Expr> {
+    let (rest, first) = conjunction(input)?;
+    let (rest, others) = many0(preceded(or_sep, cut(conjunction)))(rest)?;
+    Ok((rest, fold_binary(first, others, BinaryOperator::Or)))
+}
+
+fn table_ref(input: &str) -> Res<'_, TableRef> {
+    let (rest, name) = context("expected a table name", identifier)(input)?;
+    let (rest, alias) = opt(alias)(rest)?;
+    Ok((
+        rest,
+        TableRef {
+            name: name.to_string(),
+            alias: alias.map(|x| x.to_string()),
+        },
+    ))
+}
+
+fn join(input: &str) -> Res<'_, Join> {
+    let (rest, kind) = alt((
+        value(JoinKind::Inner, pair(keyword("inner"), multispace1)),
+        value(
+            JoinKind::Left,
+            tuple((
+                keyword("left"),
+                multispace1,
+                opt(pair(keyword("outer"), multispace1)),
+            )),
+        ),
+        success(JoinKind::Inner),
+    ))(input)?;
+    let (rest, _) = keyword("join")(rest)?;
+    let (rest, table) = cut(preceded(multispace1, table_ref))(rest)?;
+    let (rest, _) = cut(tuple((
+        multispace1,
+        context("expected on", keyword("on")),
+        multispace0,
+    )))(rest)?;
+    let (rest, on) = cut(condition)(rest)?;
+    Ok((rest, Join { kind, table, on }))
+}
+
+fn alias(input: &str) -> Res<'_, &str> {
+    preceded(
+        multispace1,
+        alt((
+            preceded(pair(keyword("as"), multispace1), cut(identifier)),
+            identifier,
+        )),
+    )(input)
+}
+
+fn select_item(input: &str) -> Res<'_, SelectItem> {
+    let (rest, expr) = operand(input)?;
+    let (rest, alias) = opt(alias)(rest)?;
+    Ok((
+        rest,
+        SelectItem::Expr {
+            expr,
+            alias: alias.map(|x| x.to_string()),
+        },
+    ))
+}
+
+fn select_list(input: &str) -> Res<'_, Vec<SelectItem>> {
+    context(
+        "expected * or a list of columns",
+        alt((
+            value(vec![SelectItem::Wildcard], char('*')),
+            separated_list1(delimited(multispace0, char(','), multispace0), select_item),
+        )),
+    )(input)
+}
+
+fn order_by_expr(input: &str) -> Res<'_, OrderByExpr> {
+    let (rest, expr) = operand(input)?;
+    let (rest, dir) = opt(preceded(
+        multispace1,
+        alt((value(true, keyword("asc")), value(false, keyword("desc")))),
+    ))(rest)?;
+    Ok((
+        rest,
+        OrderByExpr {
+            expr,
+            asc: dir.unwrap_or(true),
+        },
+    ))
+}
+
+fn group_by(input: &str) -> Res<'_, Vec<Expr>> {
+    let (rest, _) = tuple((keyword("group"), multispace1))(input)?;
+    cut(preceded(
+        tuple((keyword("by"), multispace1)),
+        separated_list1(delimited(multispace0, char(','), multispace0), operand),
+    ))(rest)
+}
+
+fn having(input: &str) -> Res<'_, Expr> {
+    let (rest, _) = pair(keyword("having"), multispace0)(input)?;
+    cut(condition)(rest)
+}
+
+fn order_by(input: &str) -> Res<'_, Vec<OrderByExpr>> {
+    let (rest, _) = tuple((keyword("order"), multispace1))(input)?;
+    cut(preceded(
+        tuple((keyword("by"), multispace1)),
+        separated_list1(
+            delimited(multispace0, char(','), multispace0),
+            order_by_expr,
+        ),
+    ))(rest)
+}
+
+fn count(input: &str) -> Res<'_, u64> {
+    context(
+        "expected a number",
+        map_res(digit1, |x: &str| x.parse::<u64>()),
+    )(input)
+}
+
+fn limit(input: &str) -> Res<'_, u64> {
+    let (rest, _) = keyword("limit")(input)?;
+    cut(preceded(multispace1, count))(rest)
+}
+
+fn offset(input: &str) -> Res<'_, u64> {
+    let (rest, _) = keyword("offset")(input)?;
+    cut(preceded(multispace1, count))(rest)
+}
+
+fn statement(input: &str) -> Res<'_, Statement> {
+    let (rest, _) = preceded(multispace0, context("expected select", keyword("select")))(input)?;
+    let (rest, projection) = cut(preceded(multispace1, select_list))(rest)?;
+    let (rest, _) = cut(preceded(
+        multispace1,
+        context("expected from", keyword("from")),
+    ))(rest)?;
+    let (rest, from) = cut(preceded(multispace1, table_ref))(rest)?;
+    let (rest, joins) = many0(preceded(multispace1, join))(rest)?;
+    let (rest, selection) = opt(preceded(
+        tuple((multispace1, keyword("where"), multispace0)),
+        cut(condition),
+    ))(rest)?;
+    let (rest, group_by) = opt(preceded(multispace1, group_by))(rest)?;
+    let (rest, having) = opt(preceded(multispace1, having))(rest)?;
+    let (rest, order_by) = opt(preceded(multispace1, order_by))(rest)?;
+    let (rest, (limit, offset)) = alt((
+        map(
+            pair(
+                preceded(multispace1, limit),
+                opt(preceded(multispace1, offset)),
+            ),
+            |(l, o)| (Some(l), o),
+        ),
+        map(
+            pair(
+                preceded(multispace1, offset),
+                opt(preceded(multispace1, limit)),
+            ),
+            |(o, l)| (l, Some(o)),
+        ),
+        success((None, None)),
+    ))(rest)?;
+    let (rest, _) = tuple((multispace0, opt(char(';')), multispace0))(rest)?;
+
+    Ok((
+        rest,
+        Statement {
+            projection,
+            from,
+            joins,
+            selection,
+            group_by: group_by.unwrap_or_default(),
+            having,
+            order_by: order_by.unwrap_or_default(),
+            limit,
+            offset,
+        },
+    ))
+}
+
+fn to_parse_error(input: &str, e: VerboseError<&str>) -> ParseError {
+    let position = e
+        .errors
+        .first()
+        .map(|(rest, _)| input.len() - rest.len())
+        .unwrap_or(0);
+    let message = e
+        .errors
+        .iter()
+        .find_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(c) => Some(c.to_string()),
+            _ => None,
+        })
+        .unwrap_or_else(|| "unexpected input".to_string());
+    ParseError { position, message }
+}
+
+/// Parses a `select <cols> from <table> [where ..] [group by ..] [having ..] [order by ..]
+/// [limit n] [offset m];` query.
+pub fn parse_statement(input: &str) -> Result<Statement, ParseError> {
+    let res = all_consuming(statement)(input).map_err(|e| match e {
+        nom::Err::Error(e) | nom::Err::Failure(e) => to_parse_error(input, e),
+        nom::Err::Incomplete(_) => ParseError {
+            position: input.len(),
+            message: "unexpected end of query".to_string(),
+        },
+    });
+    res.map(|(_, stmt)| stmt)
+}
+
+/// Splits a script into statements at each `;` that isn't inside a string.
+/// Each statement keeps its `;`, and a last one without one is kept too.
+pub fn split_statements(script: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    for (i, c) in script.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, ';') => {
+                out.push(script[start..=i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    out.push(script[start..].trim());
+    out.retain(|x| !x.is_empty() && *x != ";");
+    out
+}
+
+/// True if `input` ends with a `;` outside of any string, so there's nothing
+/// left to wait for before running it.
+pub fn is_complete(input: &str) -> bool {
+    let mut quote = None;
+    for c in input.chars() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            _ => (),
+        }
+    }
+    quote.is_none() && input.trim_end().ends_with(';')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn col(name: &str) -> Box<Expr> {
+        Box::new(Expr::Column(name.to_string()))
+    }
+
+    fn int(n: i64) -> Box<Expr> {
+        Box::new(Expr::Literal(Literal::Integer(n)))
+    }
+
+    fn eq(column: &str, n: i64) -> Expr {
+        Expr::BinaryOp {
+            left: col(column),
+            op: BinaryOperator::Eq,
+            right: int(n),
+        }
+    }
+
+    fn binary(left: Expr, op: BinaryOperator, right: Expr) -> Expr {
+        Expr::BinaryOp {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        }
+    }
+
+    fn selection(sql: &str) -> Expr {
+        parse_statement(sql).unwrap().selection.unwrap()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let expected = binary(
+            eq("a", 1),
+            BinaryOperator::Or,
+            binary(eq("b", 2), BinaryOperator::And, eq("c", 3)),
+        );
+        assert_eq!(
+            selection("select * from t where a = 1 or b = 2 and c = 3"),
+            expected
+        );
+        assert_eq!(
+            selection("select * from t where a = 1 or b = 2, c = 3"),
+            expected
+        );
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and() {
+        assert_eq!(
+            selection("select * from t where not a = 1 and b = 2"),
+            binary(
+                Expr::Not(Box::new(eq("a", 1))),
+                BinaryOperator::And,
+                eq("b", 2)
+            )
+        );
+        assert_eq!(
+            selection("select * from t where not (a = 1 or b = 2)"),
+            Expr::Not(Box::new(binary(eq("a", 1), BinaryOperator::Or, eq("b", 2))))
+        );
+    }
+
+    #[test]
+    fn is_null() {
+        assert_eq!(
+            selection("select * from t where a is null"),
+            Expr::IsNull {
+                expr: col("a"),
+                negated: false
+            }
+        );
+        assert_eq!(
+            selection("select * from t where a IS NOT NULL"),
+            Expr::IsNull {
+                expr: col("a"),
+                negated: true
+            }
+        );
+    }
+
+    #[test]
+    fn bare_values() {
+        assert_eq!(selection("select * from t where a"), *col("a"));
+        assert_eq!(
+            selection("select * from t where not a and b = 1"),
+            binary(Expr::Not(col("a")), BinaryOperator::And, eq("b", 1))
+        );
+        let stmt = parse_statement("select a from t group by a having count(*)").unwrap();
+        assert_eq!(
+            stmt.having,
+            Some(Expr::Function {
+                name: "count".to_string(),
+                distinct: false,
+                args: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn in_list() {
+        assert_eq!(
+            selection("select * from t where a in (1, 2)"),
+            Expr::InList {
+                expr: col("a"),
+                list: vec![*int(1), *int(2)],
+                negated: false
+            }
+        );
+        assert_eq!(
+            selection("select * from t where a not in (1)"),
+            Expr::InList {
+                expr: col("a"),
+                list: vec![*int(1)],
+                negated: true
+            }
+        );
+    }
+
+    #[test]
+    fn joins_and_aliases() {
+        let stmt = parse_statement(
+            "select p.pid as id, m.file_name from procs p left join proc_maps as m on m.pid = p.pid",
+        )
+        .unwrap();
+        assert_eq!(
+            stmt.projection,
+            vec![
+                SelectItem::Expr {
+                    expr: *col("p.pid"),
+                    alias: Some("id".to_string())
+                },
+                SelectItem::Expr {
+                    expr: *col("m.file_name"),
+                    alias: None
+                },
+            ]
+        );
+        assert_eq!(
+            stmt.from,
+            TableRef {
+                name: "procs".to_string(),
+                alias: Some("p".to_string())
+            }
+        );
+        assert_eq!(
+            stmt.joins,
+            vec![Join {
+                kind: JoinKind::Left,
+                table: TableRef {
+                    name: "proc_maps".to_string(),
+                    alias: Some("m".to_string())
+                },
+                on: binary(*col("m.pid"), BinaryOperator::Eq, *col("p.pid")),
+            }]
+        );
+        assert!(stmt.has_joins());
+    }
+
+    #[test]
+    fn limit_and_offset_in_either_order() {
+        for sql in [
+            "select * from t limit 5 offset 10",
+            "select * from t offset 10 limit 5;",
+        ] {
+            let stmt = parse_statement(sql).unwrap();
+            assert_eq!((stmt.limit, stmt.offset), (Some(5), Some(10)), "{}", sql);
+        }
+        let stmt = parse_statement("select * from t offset 3").unwrap();
+        assert_eq!((stmt.limit, stmt.offset), (None, Some(3)));
+    }
+
+    #[test]
+    fn row_budget_saturates() {
+        let stmt = parse_statement("select * from t limit 18446744073709551615 offset 1").unwrap();
+        assert_eq!(stmt.row_budget(), Some(usize::MAX));
+        let stmt = parse_statement("select * from t limit 5 offset 10").unwrap();
+        assert_eq!(stmt.row_budget(), Some(15));
+        let stmt = parse_statement("select * from t order by a limit 5").unwrap();
+        assert_eq!(stmt.row_budget(), None);
+    }
+
+    #[test]
+    fn error_positions() {
+        let cases = [
+            ("selec * from t", 0, "expected select"),
+            ("select * form t", 9, "expected from"),
+            (
+                "select * from t where a =",
+                25,
+                "expected a column, a value or a function",
+            ),
+            (
+                "select * from t where a is 1",
+                27,
+                "expected null or not null",
+            ),
+            ("select * from t where a in (1", 29, "expected )"),
+            ("select * from t limit x", 22, "expected a number"),
+        ];
+        for (sql, position, message) in cases {
+            let e = parse_statement(sql).unwrap_err();
+            assert_eq!(
+                (e.position, e.message.as_str()),
+                (position, message),
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn split_statements_ignores_quoted_semicolons() {
+        assert_eq!(
+            split_statements("select 'a;b' from t; select \"c;\" from t;\n select 1 from t"),
+            vec![
+                "select 'a;b' from t;",
+                "select \"c;\" from t;",
+                "select 1 from t"
+            ]
+        );
+        assert_eq!(split_statements(" ; ;\n"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn is_complete_ignores_quoted_semicolons() {
+        assert!(is_complete("select * from t;"));
+        assert!(is_complete("select 'a;' from t; \n"));
+        assert!(!is_complete("select * from t"));
+        assert!(!is_complete("select * from t where a = 'x;"));
+        assert!(!is_complete("select * from t where a = \"it's;"));
+    }
+}
@@ -0,0 +1,101 @@
+use super::{Column, ColumnType, Context, Table};
+use crate::constraint::{Constraint, IndexColumn, Operator};
+use crate::error::QueryError;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+pub struct Fs;
+
+const COLUMNS: [Column; 5] = [
+    Column::new("path", ColumnType::Text, "The directory being listed").hidden(),
+    Column::new("name", ColumnType::Text, "The path of the file"),
+    Column::new("dir", ColumnType::Bool, "True if the file is a directory"),
+    Column::new("uid", ColumnType::Integer, "The uid of the file owner"),
+    Column::new("created", ColumnType::Timestamp, "The file creation time"),
+];
+
+const INDEX: [IndexColumn; 1] = [IndexColumn {
+    name: "path",
+    required: true,
+    operators: &[Operator::Eq, Operator::In],
+}];
+
+impl Table for Fs {
+    fn name(&self) -> &'static str {
+        "fs"
+    }
+
+    fn description(&self) -> &'static str {
+        "Query the file system"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &COLUMNS
+    }
+
+    fn index_columns(&self) -> &'static [IndexColumn] {
+        &INDEX
+    }
+
+    fn generate(&self, context: &mut Context) -> Result<(), QueryError> {
+        let paths: Vec<String> = match context.constraints.get("path") {
+            Some(Constraint::Values(v)) => v.iter().map(|x| x.to_string()).collect(),
+            _ => Vec::new(),
+        };
+        for file_path in paths {
+            let path = context.roots.host(Path::new(&file_path));
+            let paths = match fs::read_dir(&path) {
+                Ok(p) => p,
+                Err(e) => {
+                    context.warn(&path, e);
+                    continue;
+                }
+            };
+            for entry in paths {
+                if context.is_full() {
+                    return Ok(());
+                }
+                let entry = match entry {
+                    Ok(e) => e,
+                    Err(e) => {
+                        context.warn(&path, e);
+                        continue;
+                    }
+                };
+                let p = entry.path();
+
+                // Named as it is on the machine being queried, not as it
+                // is under the root.
+                let path_str = Path::new(&file_path)
+                    .join(entry.file_name())
+                    .display()
+                    .to_string();
+                // Still list files whose metadata can't be read, like
+                // broken symlinks, just without the details.
+                let md = match p.metadata() {
+                    Ok(md) => Some(md),
+                    Err(e) => {
+                        context.warn(&p, e);
+                        None
+                    }
+                };
+
+                let ct = md
+                    .as_ref()
+                    .and_then(|md| md.created().ok())
+                    .map(DateTime::<Utc>::from);
+
+                context.add_row(vec![
+                    file_path.as_str().into(),
+                    path_str.into(),
+                    md.as_ref().map(|md| md.is_dir()).into(),
+                    md.as_ref().map(|md| md.uid()).into(),
+                    ct.into(),
+                ]);
+            }
+        }
+        Ok(())
+    }
+}
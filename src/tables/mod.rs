@@ -0,0 +1,399 @@
+mod fs;
+mod net;
+mod os_version;
+mod proc_maps;
+mod process_args;
+mod process_open_files;
+mod process_open_sockets;
+mod procs;
+
+use super::constraint::{Constraint, Constraints, IndexColumn};
+use super::engine::{Filter, HasLookup};
+use super::error::{QueryError, Warning};
+use super::value::{parse_timestamp, Value};
+use std::fmt;
+use std::fs as stdfs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// The kind of value a column holds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnType {
+    Integer,
+    Text,
+    Bool,
+    Timestamp,
+}
+
+impl ColumnType {
+    /// True if a value could be compared with a column of this type.
+    pub fn accepts(&self, v: &Value) -> bool {
+        match (self, v) {
+            (_, Value::Null) | (ColumnType::Text, _) => true,
+            (ColumnType::Integer, Value::Integer(_) | Value::Real(_)) => true,
+            (ColumnType::Integer, Value::Text(s)) => s.trim().parse::<f64>().is_ok(),
+            (ColumnType::Bool, Value::Bool(_) | Value::Integer(_)) => true,
+            (ColumnType::Bool, Value::Text(s)) => {
+                s.eq_ignore_ascii_case("true") || s.eq_ignore_ascii_case("false")
+            }
+            (ColumnType::Timestamp, Value::Timestamp(_) | Value::Integer(_)) => true,
+            (ColumnType::Timestamp, Value::Text(s)) => parse_timestamp(s).is_some(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColumnType::Integer => "integer",
+            ColumnType::Text => "text",
+            ColumnType::Bool => "bool",
+            ColumnType::Timestamp => "timestamp",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub struct Column {
+    pub name: &'static str,
+    pub kind: ColumnType,
+    pub description: &'static str,
+    /// Hidden columns can be selected and filtered on, but `*` leaves them out.
+    pub hidden: bool,
+}
+
+impl Column {
+    pub const fn new(name: &'static str, kind: ColumnType, description: &'static str) -> Column {
+        Column {
+            name,
+            kind,
+            description,
+            hidden: false,
+        }
+    }
+
+    pub const fn hidden(self) -> Column {
+        Column {
+            hidden: true,
+            ..self
+        }
+    }
+}
+
+/// A table that can be queried. Rows are generated on demand, with values
+/// in the same order as `columns`.
+pub trait Table: Sync {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    fn columns(&self) -> &'static [Column];
+
+    /// Columns the table can use to narrow down what it reads, see
+    /// `constraint::push_down`.
+    fn index_columns(&self) -> &'static [IndexColumn] {
+        &[]
+    }
+
+    fn generate(&self, context: &mut Context) -> Result<(), QueryError>;
+
+    fn has_column(&self, name: &str) -> bool {
+        self.columns().iter().any(|x| x.name == name)
+    }
+
+    fn column_type(&self, name: &str) -> Option<ColumnType> {
+        self.columns()
+            .iter()
+            .find(|x| x.name == name)
+            .map(|x| x.kind)
+    }
+
+    /// True if the table can't be scanned unless `name` is constrained.
+    fn is_required(&self, name: &str) -> bool {
+        self.index_columns()
+            .iter()
+            .any(|x| x.name == name && x.required)
+    }
+
+    /// The columns `*` stands for.
+    fn visible_columns(&self) -> Vec<String> {
+        self.columns()
+            .iter()
+            .filter(|x| !x.hidden)
+            .map(|x| x.name.to_string())
+            .collect()
+    }
+}
+
+/// Every table, in the order `help` lists them.
+static TABLES: [&dyn Table; 8] = [
+    &procs::Procs,
+    &process_args::ProcessArgs,
+    &process_open_files::ProcessOpenFiles,
+    &process_open_sockets::ProcessOpenSockets,
+    &proc_maps::ProcMaps,
+    &net::Net,
+    &fs::Fs,
+    &os_version::OsVersion,
+];
+
+pub fn all() -> &'static [&'static dyn Table] {
+    &TABLES
+}
+
+pub fn find(name: &str) -> Option<&'static dyn Table> {
+    TABLES.iter().find(|x| x.name() == name).copied()
+}
+
+/// One generated row, looked up by the table's column names.
+pub struct Row<'a> {
+    pub columns: &'a [Column],
+    pub values: &'a [Value],
+}
+
+impl HasLookup for Row<'_> {
+    fn lookup(&self, attribute: &str) -> Value {
+        match self.columns.iter().position(|x| x.name == attribute) {
+            Some(i) => self.values[i].clone(),
+            None => Value::Null,
+        }
+    }
+}
+
+/// Where tables read the machine from. Normally that's `/` and `/proc`, but
+/// it can be a captured directory tree, a mounted disk image or a
+/// container's root instead.
+#[derive(Clone, Debug)]
+pub struct Roots {
+    root: PathBuf,
+    proc: Option<PathBuf>,
+}
+
+impl Default for Roots {
+    fn default() -> Roots {
+        Roots {
+            root: PathBuf::from("/"),
+            proc: None,
+        }
+    }
+}
+
+impl Roots {
+    /// Reads everything under `root`, including procfs at `<root>/proc`
+    /// unless `set_proc` says otherwise.
+    pub fn set_root(&mut self, root: impl Into<PathBuf>) {
+        self.root = root.into();
+    }
+
+    pub fn set_proc(&mut self, proc: impl Into<PathBuf>) {
+        self.proc = Some(proc.into());
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where procfs is read from.
+    pub fn proc_root(&self) -> PathBuf {
+        match &self.proc {
+            Some(p) => p.clone(),
+            None => self.host(Path::new("/proc")),
+        }
+    }
+
+    /// Where an absolute path on the machine being queried really is.
+    pub fn host(&self, path: &Path) -> PathBuf {
+        if self.root == Path::new("/") {
+            return path.to_path_buf();
+        }
+        self.root.join(path.strip_prefix("/").unwrap_or(path))
+    }
+
+    /// Where a file under procfs really is, e.g. `proc("net/tcp")`.
+    pub fn proc(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.proc_root().join(path)
+    }
+}
+
+/// What a table gets while generating rows: the constraints on its index
+/// columns, where to read from, and somewhere to put the rows that pass the
+/// rest of the where clause.
+pub struct Context {
+    pub constraints: Constraints,
+    pub roots: Roots,
+    filters: Filter,
+    budget: Option<usize>,
+    columns: &'static [Column],
+    rows: Vec<Vec<Value>>,
+    warnings: Vec<Warning>,
+}
+
+impl Context {
+    pub fn new(
+        table: &dyn Table,
+        constraints: Constraints,
+        filters: Filter,
+        budget: Option<usize>,
+        roots: Roots,
+    ) -> Context {
+        Context {
+            constraints,
+            roots,
+            filters,
+            budget,
+            columns: table.columns(),
+            rows: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Keeps the row if it passes the where clause.
+    pub fn add_row(&mut self, values: Vec<Value>) {
+        let row = Row {
+            columns: self.columns,
+            values: &values,
+        };
+        if self.filters.check(&row) {
+            self.rows.push(values);
+        }
+    }
+
+    /// True once the query has all the rows it needs, so a table can stop.
+    pub fn is_full(&self) -> bool {
+        self.budget.is_some_and(|n| self.rows.len() >= n)
+    }
+
+    /// Notes something that couldn't be read, to show after the results.
+    pub fn warn(&mut self, path: &Path, reason: impl fmt::Display) {
+        self.warnings.push(Warning::new(path, reason));
+    }
+
+    /// The rows kept, and the warnings from generating them.
+    pub fn finish(self) -> (Vec<Vec<Value>>, Vec<Warning>) {
+        (self.rows, self.warnings)
+    }
+}
+
+/// Reads a file, or warns and returns `None` if it can't be read.
+fn read_file(context: &mut Context, path: &Path) -> Option<String> {
+    match stdfs::read_to_string(path) {
+        Ok(c) => Some(c),
+        Err(e) => {
+            context.warn(path, e);
+            None
+        }
+    }
+}
+
+/// The arguments a process was started with, from `/proc/<pid>/cmdline`
+/// where each one ends in a NUL. Kernel threads have none.
+fn read_args(context: &mut Context, pid: u32) -> Option<Vec<String>> {
+    let path = context.roots.proc(format!("{}/cmdline", pid));
+    let bytes = match stdfs::read(&path) {
+        Ok(b) => b,
+        Err(e) => {
+            context.warn(&path, e);
+            return None;
+        }
+    };
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(&bytes);
+    if bytes.is_empty() {
+        return Some(Vec::new());
+    }
+    Some(
+        bytes
+            .split(|b| *b == 0)
+            .map(|x| String::from_utf8_lossy(x).into_owned())
+            .collect(),
+    )
+}
+
+/// Follows a link like `/proc/<pid>/exe`. Kernel threads have none, so only
+/// other failures, mostly not being allowed to look, are warned about.
+fn read_link(context: &mut Context, path: &Path) -> Option<PathBuf> {
+    match stdfs::read_link(path) {
+        Ok(p) => Some(p),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => {
+            context.warn(path, e);
+            None
+        }
+    }
+}
+
+/// A process's open file descriptors and what they point at, from the links
+/// in `/proc/<pid>/fd`, in fd order. Usually only the process's owner and
+/// root are allowed to look.
+fn read_fds(context: &mut Context, pid: u32) -> Vec<(u32, PathBuf)> {
+    let dir = context.roots.proc(format!("{}/fd", pid));
+    let entries = match stdfs::read_dir(&dir) {
+        Ok(e) => e,
+        Err(e) => {
+            context.warn(&dir, e);
+            return Vec::new();
+        }
+    };
+    let mut fds: Vec<(u32, PathBuf)> = Vec::new();
+    for entry in entries {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(e) => {
+                context.warn(&dir, e);
+                continue;
+            }
+        };
+        let path = entry.path();
+        let fd = match get_pids(&path) {
+            Some(fd) => fd,
+            None => continue,
+        };
+        match stdfs::read_link(&path) {
+            Ok(target) => fds.push((fd, target)),
+            // The fd was closed since the directory was read.
+            Err(e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => context.warn(&path, e),
+        }
+    }
+    fds.sort_by_key(|x| x.0);
+    fds
+}
+
+fn get_pids(x: &Path) -> Option<u32> {
+    x.file_name()?.to_str()?.parse().ok()
+}
+
+fn dir_to_list(path: &Path) -> Result<Vec<PathBuf>, QueryError> {
+    let paths = stdfs::read_dir(path).map_err(|e| QueryError::io(path, e))?;
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for entry in paths.flatten() {
+        let p = entry.path();
+        if p.is_dir() {
+            dirs.push(p);
+        }
+    }
+    Ok(dirs)
+}
+
+/// The pids a scan should read: the ones a constraint lists, or every
+/// running process that matches it.
+fn select_pids(roots: &Roots, constraint: Option<&Constraint>) -> Result<Vec<u32>, QueryError> {
+    let mut pids: Vec<u32> = Vec::new();
+    match constraint {
+        Some(Constraint::Values(values)) => {
+            for p in values.iter().filter_map(|x| x.to_string().parse().ok()) {
+                if !pids.contains(&p) {
+                    pids.push(p);
+                }
+            }
+        }
+        c => {
+            for pd in dir_to_list(&roots.proc_root())? {
+                if let Some(p) = get_pids(&pd) {
+                    if c.is_none_or(|c| c.matches(&p.into())) {
+                        pids.push(p);
+                    }
+                }
+            }
+        }
+    }
+    Ok(pids)
+}
@@ -0,0 +1,208 @@
+use super::{Column, ColumnType, Context, Table};
+use crate::error::QueryError;
+use std::fs;
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+pub struct Net;
+
+const COLUMNS: [Column; 16] = [
+    Column::new(
+        "protocol",
+        ColumnType::Text,
+        "Network Protocol (tcp or udp)",
+    ),
+    Column::new("family", ColumnType::Text, "ipv4 or ipv6"),
+    Column::new("local_address", ColumnType::Text, "Our address"),
+    Column::new("local_port", ColumnType::Integer, "Our port"),
+    Column::new("remote_address", ColumnType::Text, "Their address"),
+    Column::new("remote_port", ColumnType::Integer, "Their port"),
+    Column::new(
+        "state",
+        ColumnType::Text,
+        "Connection State, e.g. LISTEN or ESTABLISHED",
+    ),
+    Column::new("tx_queue", ColumnType::Integer, "Bytes waiting to be sent"),
+    Column::new("rx_queue", ColumnType::Integer, "Bytes waiting to be read"),
+    Column::new(
+        "uid",
+        ColumnType::Integer,
+        "uid of the owner of the network connection",
+    ),
+    Column::new("timeout", ColumnType::Integer, "Timeout"),
+    Column::new("inode", ColumnType::Integer, "Inode"),
+    Column::new("ref_cnt", ColumnType::Integer, "Reference Count"),
+    Column::new(
+        "raw_local_address",
+        ColumnType::Text,
+        "Our address and port in hex, as in /proc/net",
+    )
+    .hidden(),
+    Column::new(
+        "raw_remote_address",
+        ColumnType::Text,
+        "Their address and port in hex, as in /proc/net",
+    )
+    .hidden(),
+    Column::new(
+        "st",
+        ColumnType::Text,
+        "Connection State in hex, as in /proc/net",
+    )
+    .hidden(),
+];
+
+/// The socket files under `/proc/net`, with their protocol and family.
+const SOCKET_FILES: [(&str, &str, &str); 4] = [
+    ("tcp", "tcp", "ipv4"),
+    ("tcp6", "tcp", "ipv6"),
+    ("udp", "udp", "ipv4"),
+    ("udp6", "udp", "ipv6"),
+];
+
+/// One line of `/proc/net/tcp` and friends, with the addresses and state
+/// still as the kernel writes them.
+pub(super) struct Socket {
+    pub protocol: &'static str,
+    pub family: &'static str,
+    pub local: String,
+    pub remote: String,
+    pub state: String,
+    pub tx_queue: Option<u64>,
+    pub rx_queue: Option<u64>,
+    pub uid: Option<u32>,
+    pub timeout: Option<u64>,
+    pub inode: Option<u64>,
+    pub ref_cnt: Option<u64>,
+}
+
+fn parse_socket(line: &str, protocol: &'static str, family: &'static str) -> Option<Socket> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 11 || !fields[0].ends_with(':') {
+        return None;
+    }
+    let (tx, rx) = fields[4].split_once(':')?;
+    Some(Socket {
+        protocol,
+        family,
+        local: fields[1].to_string(),
+        remote: fields[2].to_string(),
+        state: fields[3].to_string(),
+        tx_queue: u64::from_str_radix(tx, 16).ok(),
+        rx_queue: u64::from_str_radix(rx, 16).ok(),
+        uid: fields[7].parse().ok(),
+        timeout: fields[8].parse().ok(),
+        inode: fields[9].parse().ok(),
+        ref_cnt: fields[10].parse().ok(),
+    })
+}
+
+/// Every TCP and UDP socket, IPv4 and IPv6. Files that can't be read are
+/// warned about and skipped, except the IPv6 ones going missing, which just
+/// means IPv6 is turned off.
+pub(super) fn read_sockets(context: &mut Context) -> Vec<Socket> {
+    let mut sockets = Vec::new();
+    for (file, protocol, family) in SOCKET_FILES {
+        let path = context.roots.proc(format!("net/{}", file));
+        let res = match fs::read_to_string(&path) {
+            Ok(r) => r,
+            Err(e) if family == "ipv6" && e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => {
+                context.warn(&path, e);
+                continue;
+            }
+        };
+        sockets.extend(
+            res.lines()
+                .filter_map(|l| parse_socket(l, protocol, family)),
+        );
+    }
+    sockets
+}
+
+/// Turns `0100007F:0035` into `127.0.0.1` and 53. The address is in the
+/// kernel's byte order, 32 bits at a time, and the port is big endian.
+pub(super) fn decode_address(raw: &str) -> Option<(String, u16)> {
+    let (addr, port) = raw.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let words: Vec<u32> = (0..addr.len() / 8)
+        .map(|i| {
+            addr.get(i * 8..i * 8 + 8)
+                .and_then(|w| u32::from_str_radix(w, 16).ok())
+        })
+        .collect::<Option<_>>()?;
+    let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_ne_bytes()).collect();
+    let addr = match bytes.len() {
+        4 => Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).to_string(),
+        16 => {
+            let b: [u8; 16] = bytes.try_into().ok()?;
+            Ipv6Addr::from(b).to_string()
+        }
+        _ => return None,
+    };
+    Some((addr, port))
+}
+
+/// The name of a TCP state code, as in include/net/tcp_states.h. UDP uses
+/// the same codes, ESTABLISHED when connected and CLOSE otherwise.
+pub(super) fn state_name(code: &str) -> Option<&'static str> {
+    let name = match u8::from_str_radix(code, 16).ok()? {
+        1 => "ESTABLISHED",
+        2 => "SYN_SENT",
+        3 => "SYN_RECV",
+        4 => "FIN_WAIT1",
+        5 => "FIN_WAIT2",
+        6 => "TIME_WAIT",
+        7 => "CLOSE",
+        8 => "CLOSE_WAIT",
+        9 => "LAST_ACK",
+        10 => "LISTEN",
+        11 => "CLOSING",
+        12 => "NEW_SYN_RECV",
+        _ => return None,
+    };
+    Some(name)
+}
+
+impl Table for Net {
+    fn name(&self) -> &'static str {
+        "net"
+    }
+
+    fn description(&self) -> &'static str {
+        "Network connections"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &COLUMNS
+    }
+
+    fn generate(&self, context: &mut Context) -> Result<(), QueryError> {
+        for socket in read_sockets(context) {
+            if context.is_full() {
+                break;
+            }
+            let local = decode_address(&socket.local);
+            let remote = decode_address(&socket.remote);
+            context.add_row(vec![
+                socket.protocol.into(),
+                socket.family.into(),
+                local.as_ref().map(|x| x.0.as_str()).into(),
+                local.map(|x| x.1 as u32).into(),
+                remote.as_ref().map(|x| x.0.as_str()).into(),
+                remote.map(|x| x.1 as u32).into(),
+                state_name(&socket.state).into(),
+                socket.tx_queue.into(),
+                socket.rx_queue.into(),
+                socket.uid.into(),
+                socket.timeout.into(),
+                socket.inode.into(),
+                socket.ref_cnt.into(),
+                socket.local.into(),
+                socket.remote.into(),
+                socket.state.into(),
+            ]);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use super::{read_file, Column, ColumnType, Context, Table};
+use crate::error::QueryError;
+use crate::value::Value;
+use regex::Regex;
+
+pub struct OsVersion;
+
+const COLUMNS: [Column; 3] = [
+    Column::new(
+        "kernel_version",
+        ColumnType::Text,
+        "The version of the Linux kernel in use",
+    ),
+    Column::new(
+        "build_id",
+        ColumnType::Text,
+        "The user and host that built the kernel",
+    ),
+    Column::new(
+        "gcc_version",
+        ColumnType::Text,
+        "The version of the gcc compiler in use",
+    ),
+];
+
+impl Table for OsVersion {
+    fn name(&self) -> &'static str {
+        "os_version"
+    }
+
+    fn description(&self) -> &'static str {
+        "Query the operating system version"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &COLUMNS
+    }
+
+    fn generate(&self, context: &mut Context) -> Result<(), QueryError> {
+        let path = context.roots.proc("version");
+        let res = match read_file(context, &path) {
+            Some(r) => r,
+            None => {
+                context.add_row(vec![Value::Null, Value::Null, Value::Null]);
+                return Ok(());
+            }
+        };
+        // e.g. `Linux version 6.1.0-13-amd64 (user@host) (gcc-12 (Debian
+        // 12.2.0-14) 12.2.0, GNU ld (GNU Binutils for Debian) 2.40) #1 SMP ...`
+        let version_re = Regex::new(r"version\s(\S+)").unwrap();
+        let build_re = Regex::new(r"\(([^()\s]+@[^()\s]+)\)").unwrap();
+        let gcc_re = Regex::new(r"\((gcc.*?)(?:, GNU ld.*?)?\)\s+#").unwrap();
+        let capture = |re: &Regex| {
+            re.captures(&res)
+                .and_then(|c| c.get(1))
+                .map_or("", |x| x.as_str())
+        };
+
+        let version = capture(&version_re);
+        let build_id = capture(&build_re);
+        let gcc_v = capture(&gcc_re);
+
+        context.add_row(vec![version.into(), build_id.into(), gcc_v.into()]);
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use super::{read_file, select_pids, Column, ColumnType, Context, Table};
+use crate::constraint::{IndexColumn, Operator};
+use crate::error::QueryError;
+use regex::Regex;
+
+pub struct ProcMaps;
+
+const COLUMNS: [Column; 10] = [
+    Column::new("pid", ColumnType::Integer, "The process ID"),
+    Column::new(
+        "start_addr",
+        ColumnType::Text,
+        "Start of mapping in process vm",
+    ),
+    Column::new("end_addr", ColumnType::Text, "End of mapping in process vm"),
+    Column::new(
+        "permissions",
+        ColumnType::Text,
+        "Permissions (rwx) for the section",
+    ),
+    // `offset` is a keyword, so it couldn't be selected by that name.
+    Column::new(
+        "file_offset",
+        ColumnType::Text,
+        "Offset into the mapped file, in hex",
+    ),
+    Column::new(
+        "device",
+        ColumnType::Text,
+        "Device of the mapped file, as major:minor",
+    ),
+    Column::new(
+        "inode",
+        ColumnType::Integer,
+        "Inode of the mapped file, 0 if there is none",
+    ),
+    Column::new(
+        "file_name",
+        ColumnType::Text,
+        "The name of the file mapped into memory if applicable",
+    ),
+    Column::new("size_maybe", ColumnType::Text, "Old name for file_offset").hidden(),
+    Column::new("file_desc", ColumnType::Integer, "Old name for inode").hidden(),
+];
+
+const INDEX: [IndexColumn; 1] = [IndexColumn {
+    name: "pid",
+    required: true,
+    operators: &[Operator::Eq, Operator::In, Operator::Like],
+}];
+
+impl Table for ProcMaps {
+    fn name(&self) -> &'static str {
+        "proc_maps"
+    }
+
+    fn description(&self) -> &'static str {
+        "Data mapped into process memory"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &COLUMNS
+    }
+
+    fn index_columns(&self) -> &'static [IndexColumn] {
+        &INDEX
+    }
+
+    fn generate(&self, context: &mut Context) -> Result<(), QueryError> {
+        let proc_entry_re = Regex::new(
+            r"([0-9|a-f]+)\-([0-9|a-f]+)\s(\S{4})\s([0-9|a-f]+)\s(\d+:\d+)\s(\d+)\s+(\S+)?$",
+        )
+        .unwrap();
+        for pid in select_pids(&context.roots, context.constraints.get("pid"))? {
+            let path = context.roots.proc(format!("{}/maps", pid));
+            if !path.exists() {
+                continue;
+            }
+
+            let res = match read_file(context, &path) {
+                Some(r) => r,
+                None => continue,
+            };
+            for l in res.lines() {
+                if context.is_full() {
+                    return Ok(());
+                }
+                let mut capts = proc_entry_re.captures_iter(l);
+                let pm = capts.next();
+                if let Some(p) = pm {
+                    let file_name: String = match p.get(7) {
+                        Some(_) => p[7].to_string(),
+                        _ => "n/a".to_string(),
+                    };
+
+                    let inode = p[6].parse::<u64>().ok();
+                    context.add_row(vec![
+                        pid.into(),
+                        p[1].into(),
+                        p[2].into(),
+                        p[3].into(),
+                        p[4].into(),
+                        p[5].into(),
+                        inode.into(),
+                        file_name.into(),
+                        p[4].into(),
+                        inode.into(),
+                    ]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use super::{read_args, select_pids, Column, ColumnType, Context, Table};
+use crate::constraint::{IndexColumn, Operator};
+use crate::error::QueryError;
+
+pub struct ProcessArgs;
+
+const COLUMNS: [Column; 3] = [
+    Column::new("pid", ColumnType::Integer, "The process ID"),
+    Column::new(
+        "index",
+        ColumnType::Integer,
+        "The position of the argument, 0 being the program",
+    ),
+    Column::new("argument", ColumnType::Text, "The argument"),
+];
+
+const INDEX: [IndexColumn; 1] = [IndexColumn {
+    name: "pid",
+    required: false,
+    operators: &[Operator::Eq, Operator::In],
+}];
+
+impl Table for ProcessArgs {
+    fn name(&self) -> &'static str {
+        "process_args"
+    }
+
+    fn description(&self) -> &'static str {
+        "The arguments each process was started with, one per row"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &COLUMNS
+    }
+
+    fn index_columns(&self) -> &'static [IndexColumn] {
+        &INDEX
+    }
+
+    fn generate(&self, context: &mut Context) -> Result<(), QueryError> {
+        for pid in select_pids(&context.roots, context.constraints.get("pid"))? {
+            if !context.roots.proc(pid.to_string()).exists() {
+                continue;
+            }
+            let args = match read_args(context, pid) {
+                Some(a) => a,
+                None => continue,
+            };
+            for (i, arg) in args.into_iter().enumerate() {
+                if context.is_full() {
+                    return Ok(());
+                }
+                context.add_row(vec![pid.into(), (i as u64).into(), arg.into()]);
+            }
+        }
+        Ok(())
+    }
+}
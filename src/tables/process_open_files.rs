@@ -0,0 +1,98 @@
+use super::{read_fds, select_pids, Column, ColumnType, Context, Table};
+use crate::constraint::{IndexColumn, Operator};
+use crate::error::QueryError;
+use std::fs;
+
+pub struct ProcessOpenFiles;
+
+const COLUMNS: [Column; 6] = [
+    Column::new("pid", ColumnType::Integer, "The process ID"),
+    Column::new("fd", ColumnType::Integer, "The file descriptor number"),
+    Column::new(
+        "path",
+        ColumnType::Text,
+        "What the descriptor points at, e.g. a path or socket:[inode]",
+    ),
+    Column::new("type", ColumnType::Text, "file, socket, pipe or anon_inode"),
+    Column::new(
+        "flags",
+        ColumnType::Text,
+        "The flags the file was opened with, in octal as in fdinfo",
+    ),
+    Column::new(
+        "pos",
+        ColumnType::Integer,
+        "The offset the next read or write happens at",
+    ),
+];
+
+const INDEX: [IndexColumn; 1] = [IndexColumn {
+    name: "pid",
+    required: false,
+    operators: &[Operator::Eq, Operator::In],
+}];
+
+/// What kind of thing a descriptor's link points at. Only files have real
+/// paths, the rest look like `socket:[1234]`.
+fn fd_type(path: &str) -> &'static str {
+    if path.starts_with("socket:") {
+        "socket"
+    } else if path.starts_with("pipe:") {
+        "pipe"
+    } else if path.starts_with("anon_inode:") {
+        "anon_inode"
+    } else {
+        "file"
+    }
+}
+
+impl Table for ProcessOpenFiles {
+    fn name(&self) -> &'static str {
+        "process_open_files"
+    }
+
+    fn description(&self) -> &'static str {
+        "Files, sockets and pipes each process has open"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &COLUMNS
+    }
+
+    fn index_columns(&self) -> &'static [IndexColumn] {
+        &INDEX
+    }
+
+    fn generate(&self, context: &mut Context) -> Result<(), QueryError> {
+        for pid in select_pids(&context.roots, context.constraints.get("pid"))? {
+            if !context.roots.proc(pid.to_string()).exists() {
+                continue;
+            }
+            for (fd, target) in read_fds(context, pid) {
+                if context.is_full() {
+                    return Ok(());
+                }
+                let path = target.display().to_string();
+                // The fd may have closed since, which just leaves these empty.
+                let info = fs::read_to_string(context.roots.proc(format!("{}/fdinfo/{}", pid, fd)))
+                    .unwrap_or_default();
+                let field = |key: &str| {
+                    info.lines()
+                        .find_map(|l| l.strip_prefix(key))
+                        .map(|x| x.trim().to_string())
+                };
+                let flags = field("flags:");
+                let pos = field("pos:").and_then(|x| x.parse::<u64>().ok());
+                context.add_row(vec![
+                    pid.into(),
+                    fd.into(),
+                    path.as_str().into(),
+                    fd_type(&path).into(),
+                    flags.into(),
+                    pos.into(),
+                ]);
+            }
+        }
+        Ok(())
+    }
+}
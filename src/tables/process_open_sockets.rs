@@ -0,0 +1,108 @@
+use super::net::{decode_address, read_sockets, state_name, Socket};
+use super::{read_fds, read_link, select_pids, Column, ColumnType, Context, Table};
+use crate::constraint::{IndexColumn, Operator};
+use crate::error::QueryError;
+use std::collections::HashMap;
+
+pub struct ProcessOpenSockets;
+
+const COLUMNS: [Column; 11] = [
+    Column::new("pid", ColumnType::Integer, "The process ID"),
+    Column::new("fd", ColumnType::Integer, "The file descriptor number"),
+    Column::new("socket", ColumnType::Integer, "The inode of the socket"),
+    Column::new("family", ColumnType::Text, "ipv4 or ipv6"),
+    Column::new("protocol", ColumnType::Text, "tcp or udp"),
+    Column::new("local_address", ColumnType::Text, "Our address"),
+    Column::new("local_port", ColumnType::Integer, "Our port"),
+    Column::new("remote_address", ColumnType::Text, "Their address"),
+    Column::new("remote_port", ColumnType::Integer, "Their port"),
+    Column::new(
+        "state",
+        ColumnType::Text,
+        "The connection state, e.g. LISTEN or ESTABLISHED",
+    ),
+    Column::new("path", ColumnType::Text, "The path of the program"),
+];
+
+const INDEX: [IndexColumn; 1] = [IndexColumn {
+    name: "pid",
+    required: false,
+    operators: &[Operator::Eq, Operator::In],
+}];
+
+/// The inode in a descriptor link like `socket:[1234]`.
+fn socket_inode(target: &str) -> Option<u64> {
+    target
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+impl Table for ProcessOpenSockets {
+    fn name(&self) -> &'static str {
+        "process_open_sockets"
+    }
+
+    fn description(&self) -> &'static str {
+        "TCP and UDP sockets and the processes that have them open"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &COLUMNS
+    }
+
+    fn index_columns(&self) -> &'static [IndexColumn] {
+        &INDEX
+    }
+
+    fn generate(&self, context: &mut Context) -> Result<(), QueryError> {
+        let sockets: HashMap<u64, Socket> = read_sockets(context)
+            .into_iter()
+            .filter_map(|s| Some((s.inode?, s)))
+            .collect();
+        for pid in select_pids(&context.roots, context.constraints.get("pid"))? {
+            let pd = context.roots.proc(pid.to_string());
+            if !pd.exists() {
+                continue;
+            }
+            // Unix sockets and the like aren't in /proc/net/{tcp,udp}.
+            let open: Vec<(u32, &Socket)> = read_fds(context, pid)
+                .into_iter()
+                .filter_map(|(fd, target)| {
+                    let inode = socket_inode(&target.to_string_lossy())?;
+                    Some((fd, sockets.get(&inode)?))
+                })
+                .collect();
+            if open.is_empty() {
+                continue;
+            }
+            // As in procs, without the " (deleted)" on a removed program.
+            let path = read_link(context, &pd.join("exe")).map(|p| {
+                let p = p.display().to_string();
+                p.trim_end_matches(" (deleted)").to_string()
+            });
+            for (fd, socket) in open {
+                if context.is_full() {
+                    return Ok(());
+                }
+                let local = decode_address(&socket.local);
+                let remote = decode_address(&socket.remote);
+                context.add_row(vec![
+                    pid.into(),
+                    fd.into(),
+                    socket.inode.into(),
+                    socket.family.into(),
+                    socket.protocol.into(),
+                    local.as_ref().map(|x| x.0.as_str()).into(),
+                    local.map(|x| x.1 as u32).into(),
+                    remote.as_ref().map(|x| x.0.as_str()).into(),
+                    remote.map(|x| x.1 as u32).into(),
+                    state_name(&socket.state).into(),
+                    path.as_deref().into(),
+                ]);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+use super::{read_args, read_file, read_link, select_pids, Column, ColumnType, Context, Table};
+use crate::constraint::{IndexColumn, Operator};
+use crate::error::QueryError;
+use chrono::{TimeZone, Utc};
+use std::path::Path;
+
+pub struct Procs;
+
+/// Clock ticks per second, which the kernel fixes at 100 for everything it
+/// reports to user space.
+const TICKS: u64 = 100;
+
+const COLUMNS: [Column; 23] = [
+    Column::new("pid", ColumnType::Integer, "The Process ID"),
+    Column::new("ppid", ColumnType::Integer, "The parent process ID"),
+    Column::new("name", ColumnType::Text, "The name of the program"),
+    Column::new(
+        "cmdline",
+        ColumnType::Text,
+        "The command used to run the program, its arguments joined by spaces",
+    ),
+    Column::new(
+        "state",
+        ColumnType::Text,
+        "R running, S sleeping, D waiting on disk, Z zombie, T stopped, I idle",
+    ),
+    Column::new(
+        "uid",
+        ColumnType::Integer,
+        "The ID of the user who ran the program",
+    ),
+    Column::new(
+        "euid",
+        ColumnType::Integer,
+        "The user ID the program runs as",
+    ),
+    Column::new("suid", ColumnType::Integer, "The saved user ID"),
+    Column::new(
+        "gid",
+        ColumnType::Integer,
+        "The ID of the group that ran the program",
+    ),
+    Column::new(
+        "egid",
+        ColumnType::Integer,
+        "The group ID the program runs as",
+    ),
+    Column::new("sgid", ColumnType::Integer, "The saved group ID"),
+    Column::new("threads", ColumnType::Integer, "The number of threads"),
+    Column::new("nice", ColumnType::Integer, "The nice value, -20 to 19"),
+    Column::new(
+        "priority",
+        ColumnType::Integer,
+        "The scheduling priority the kernel gives the process",
+    ),
+    Column::new(
+        "rss",
+        ColumnType::Integer,
+        "Bytes of memory resident in RAM",
+    ),
+    Column::new(
+        "vsize",
+        ColumnType::Integer,
+        "Bytes of virtual memory mapped",
+    ),
+    Column::new(
+        "start_time",
+        ColumnType::Timestamp,
+        "When the process started",
+    ),
+    Column::new(
+        "user_time",
+        ColumnType::Integer,
+        "Milliseconds of CPU time spent in the program",
+    ),
+    Column::new(
+        "system_time",
+        ColumnType::Integer,
+        "Milliseconds of CPU time spent in the kernel for the program",
+    ),
+    Column::new("exe", ColumnType::Text, "The path of the program"),
+    Column::new("cwd", ColumnType::Text, "The working directory"),
+    Column::new(
+        "root",
+        ColumnType::Text,
+        "The root directory, which differs from / in a chroot",
+    ),
+    Column::new(
+        "on_disk",
+        ColumnType::Bool,
+        "False if the program has been deleted since it started, or nothing is at its path any more",
+    ),
+];
+
+const INDEX: [IndexColumn; 1] = [IndexColumn {
+    name: "pid",
+    required: false,
+    operators: &[Operator::Eq, Operator::In],
+}];
+
+/// The fields of `/proc/<pid>/stat` the table uses.
+struct Stat {
+    name: String,
+    state: String,
+    ppid: Option<u32>,
+    utime: Option<u64>,
+    stime: Option<u64>,
+    priority: Option<i64>,
+    nice: Option<i64>,
+    threads: Option<u64>,
+    starttime: Option<u64>,
+    vsize: Option<u64>,
+}
+
+/// Parses `/proc/<pid>/stat`. The name is in brackets and can contain
+/// spaces and brackets itself, so the fields are counted from the last `)`.
+fn parse_stat(stat: &str) -> Option<Stat> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    // Field 3, the state, is the first one after the name.
+    let fields: Vec<&str> = stat[close + 1..].split_whitespace().collect();
+    let field = |n: usize| fields.get(n - 3).copied();
+    Some(Stat {
+        name,
+        state: field(3)?.to_string(),
+        ppid: field(4).and_then(|x| x.parse().ok()),
+        utime: field(14).and_then(|x| x.parse().ok()),
+        stime: field(15).and_then(|x| x.parse().ok()),
+        priority: field(18).and_then(|x| x.parse().ok()),
+        nice: field(19).and_then(|x| x.parse().ok()),
+        threads: field(20).and_then(|x| x.parse().ok()),
+        starttime: field(22).and_then(|x| x.parse().ok()),
+        vsize: field(23).and_then(|x| x.parse().ok()),
+    })
+}
+
+/// The values on a line of `/proc/<pid>/status`, e.g. `Uid:` gives the
+/// real, effective, saved and filesystem uids.
+fn status_field<'a>(status: &'a str, key: &str) -> Vec<&'a str> {
+    status
+        .lines()
+        .find_map(|l| l.strip_prefix(key))
+        .map(|x| x.split_whitespace().collect())
+        .unwrap_or_default()
+}
+
+fn status_ids(status: &str, key: &str) -> [Option<u32>; 3] {
+    let ids = status_field(status, key);
+    let id = |i: usize| ids.get(i).and_then(|x| x.parse().ok());
+    [id(0), id(1), id(2)]
+}
+
+/// When the machine booted, in seconds since the epoch, from `btime` in
+/// `/proc/stat`.
+fn boot_time(context: &mut Context) -> Option<u64> {
+    let path = context.roots.proc("stat");
+    let stat = read_file(context, &path)?;
+    let btime = stat
+        .lines()
+        .find_map(|l| l.strip_prefix("btime "))
+        .and_then(|x| x.trim().parse().ok());
+    if btime.is_none() {
+        context.warn(&path, "no btime line");
+    }
+    btime
+}
+
+impl Table for Procs {
+    fn name(&self) -> &'static str {
+        "procs"
+    }
+
+    fn description(&self) -> &'static str {
+        "Processes running on the system"
+    }
+
+    fn columns(&self) -> &'static [Column] {
+        &COLUMNS
+    }
+
+    fn index_columns(&self) -> &'static [IndexColumn] {
+        &INDEX
+    }
+
+    fn generate(&self, context: &mut Context) -> Result<(), QueryError> {
+        let boot = boot_time(context);
+        for pid in select_pids(&context.roots, context.constraints.get("pid"))? {
+            if context.is_full() {
+                break;
+            }
+            let pd = context.roots.proc(pid.to_string());
+            if !pd.exists() {
+                continue;
+            }
+            // The process may have exited since it was listed.
+            let stat_path = pd.join("stat");
+            let stat = match read_file(context, &stat_path) {
+                Some(s) => s,
+                None => continue,
+            };
+            let stat = match parse_stat(&stat) {
+                Some(s) => s,
+                None => {
+                    context.warn(&stat_path, "couldn't parse it");
+                    continue;
+                }
+            };
+            let status = read_file(context, &pd.join("status")).unwrap_or_default();
+            let args = read_args(context, pid).unwrap_or_default();
+            let cmdline = match args.is_empty() {
+                true => stat.name.clone(),
+                false => args.join(" "),
+            };
+
+            let [uid, euid, suid] = status_ids(&status, "Uid:");
+            let [gid, egid, sgid] = status_ids(&status, "Gid:");
+            // Kernel threads have no memory of their own, so no VmRSS.
+            let rss = status_field(&status, "VmRSS:")
+                .first()
+                .and_then(|x| x.parse::<u64>().ok())
+                .map(|kb| kb * 1024);
+            let start_time = boot
+                .zip(stat.starttime)
+                .and_then(|(b, s)| Utc.timestamp_opt((b + s / TICKS) as i64, 0).single());
+            let ms = |ticks: Option<u64>| ticks.map(|t| t * 1000 / TICKS);
+
+            // A deleted program's link ends in " (deleted)".
+            let exe = read_link(context, &pd.join("exe")).map(|p| p.display().to_string());
+            let on_disk = exe
+                .as_ref()
+                .map(|e| !e.ends_with(" (deleted)") && context.roots.host(Path::new(e)).exists());
+            let exe = exe.map(|e| e.trim_end_matches(" (deleted)").to_string());
+            let cwd = read_link(context, &pd.join("cwd")).map(|p| p.display().to_string());
+            let root = read_link(context, &pd.join("root")).map(|p| p.display().to_string());
+
+            context.add_row(vec![
+                pid.into(),
+                stat.ppid.into(),
+                stat.name.into(),
+                cmdline.into(),
+                stat.state.into(),
+                uid.into(),
+                euid.into(),
+                suid.into(),
+                gid.into(),
+                egid.into(),
+                sgid.into(),
+                stat.threads.into(),
+                stat.nice.into(),
+                stat.priority.into(),
+                rss.into(),
+                stat.vsize.into(),
+                start_time.into(),
+                ms(stat.utime).into(),
+                ms(stat.stime).into(),
+                exe.into(),
+                cwd.into(),
+                root.into(),
+                on_disk.into(),
+            ]);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use super::sql::Literal;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A single cell of a table row.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Bool(bool),
+    Timestamp(DateTime<Utc>),
+    Blob(Vec<u8>),
+}
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.with_timezone(&Utc));
+    }
+    for f in [TIMESTAMP_FORMAT, "%d/%m/%Y %H:%M:%S"] {
+        if let Ok(t) = NaiveDateTime::parse_from_str(s, f) {
+            return Some(Utc.from_utc_datetime(&t));
+        }
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|t| Utc.from_utc_datetime(&t))
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(i) => Some(*i as f64),
+            Value::Real(r) => Some(*r),
+            Value::Bool(b) => Some(*b as i64 as f64),
+            Value::Text(s) => s.trim().parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+
+    /// Compares two values the way a WHERE clause does. Numbers compare
+    /// numerically (text is parsed if the other side is a number), timestamps
+    /// against timestamps, unix seconds or date strings, and anything else by
+    /// its text. Returns `None` if either side is NULL.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => None,
+            (Value::Text(a), Value::Text(b)) => Some(a.cmp(b)),
+            (Value::Blob(a), Value::Blob(b)) => Some(a.cmp(b)),
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            (Value::Bool(a), Value::Text(b)) => Some(a.to_string().cmp(&b.to_lowercase())),
+            (Value::Text(_), Value::Bool(_)) => other.compare(self).map(Ordering::reverse),
+            (Value::Timestamp(a), Value::Timestamp(b)) => Some(a.cmp(b)),
+            (Value::Timestamp(a), Value::Integer(b)) => Some(a.timestamp().cmp(b)),
+            (Value::Timestamp(a), Value::Text(b)) => match parse_timestamp(b) {
+                Some(b) => Some(a.cmp(&b)),
+                None => Some(self.to_string().cmp(b)),
+            },
+            (Value::Integer(_) | Value::Text(_), Value::Timestamp(_)) => {
+                other.compare(self).map(Ordering::reverse)
+            }
+            (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(b)),
+            _ => match (self.as_f64(), other.as_f64()) {
+                (Some(a), Some(b)) => a.partial_cmp(&b),
+                _ => Some(self.to_string().cmp(&other.to_string())),
+            },
+        }
+    }
+
+    /// Ordering used for sorting rows: NULLs first, then by `compare`.
+    pub fn sort_cmp(&self, other: &Value) -> Ordering {
+        match (self.is_null(), other.is_null()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => self.compare(other).unwrap_or(Ordering::Equal),
+        }
+    }
+
+    /// Substring match on the text form of the value.
+    pub fn like(&self, pattern: &Value) -> Option<bool> {
+        if self.is_null() || pattern.is_null() {
+            return None;
+        }
+        Some(self.to_string().contains(&pattern.to_string()))
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::Real(r) => write!(f, "{}", r),
+            Value::Text(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Timestamp(t) => write!(f, "{}", t.format(TIMESTAMP_FORMAT)),
+            Value::Blob(b) => write!(f, "{}", hex::encode(b)),
+        }
+    }
+}
+
+impl From<u32> for Value {
+    fn from(x: u32) -> Value {
+        Value::Integer(x as i64)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(x: u64) -> Value {
+        Value::Integer(x as i64)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(x: i64) -> Value {
+        Value::Integer(x)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(x: bool) -> Value {
+        Value::Bool(x)
+    }
+}
+
+impl From<String> for Value {
+    fn from(x: String) -> Value {
+        Value::Text(x)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(x: &str) -> Value {
+        Value::Text(x.to_string())
+    }
+}
+
+impl From<DateTime<Utc>> for Value {
+    fn from(x: DateTime<Utc>) -> Value {
+        Value::Timestamp(x)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(x: Option<T>) -> Value {
+        match x {
+            Some(v) => v.into(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl From<Literal> for Value {
+    fn from(x: Literal) -> Value {
+        match x {
+            Literal::Integer(i) => Value::Integer(i),
+            Literal::Real(r) => Value::Real(r),
+            Literal::String(s) => Value::Text(s),
+            Literal::Bool(b) => Value::Bool(b),
+            Literal::Blob(b) => Value::Blob(b),
+            Literal::Null => Value::Null,
+        }
+    }
+}
+
+impl From<Value> for Literal {
+    fn from(x: Value) -> Literal {
+        match x {
+            Value::Null => Literal::Null,
+            Value::Integer(i) => Literal::Integer(i),
+            Value::Real(r) => Literal::Real(r),
+            Value::Text(s) => Literal::String(s),
+            Value::Bool(b) => Literal::Bool(b),
+            Value::Blob(b) => Literal::Blob(b),
+            Value::Timestamp(_) => Literal::String(x.to_string()),
+        }
+    }
+}
@@ -0,0 +1,28 @@
+//! What the integration tests share: an engine reading the procfs captured
+//! in `tests/fixtures/root`, and a way to compare its rows.
+
+use rusty_osquery::Engine;
+
+pub fn engine() -> Engine {
+    Engine::new().with_root(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/root"))
+}
+
+/// The rows of a query with their values joined by commas, checking that
+/// nothing in the fixture failed to read.
+pub fn query(sql: &str) -> Vec<String> {
+    let result = engine().query(sql).unwrap();
+    let warnings: Vec<String> = result
+        .warnings
+        .iter()
+        .map(|w| format!("{}: {}", w.path.display(), w.reason))
+        .collect();
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    result
+        .rows
+        .iter()
+        .map(|r| {
+            let values: Vec<String> = r.iter().map(|v| v.to_string()).collect();
+            values.join(",")
+        })
+        .collect()
+}
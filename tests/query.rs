@@ -0,0 +1,215 @@
+//! Queries against the fixture procfs in `tests/fixtures/root`, for how the
+//! engine treats the statement rather than what any one table reads.
+
+mod common;
+
+use common::{engine, query};
+use rusty_osquery::QueryError;
+
+/// Why a query that should fail did.
+fn error(sql: &str) -> QueryError {
+    match engine().query(sql) {
+        Err(e) => e,
+        Ok(_) => panic!("{}: expected an error", sql),
+    }
+}
+
+fn unknown_column(sql: &str) -> String {
+    match engine().query(sql) {
+        Err(QueryError::UnknownColumn { name, .. }) => name,
+        Err(e) => panic!("{}: expected an unknown column, got {}", sql, e),
+        Ok(_) => panic!("{}: expected an unknown column", sql),
+    }
+}
+
+#[test]
+fn alias_in_where_is_unknown() {
+    assert_eq!(
+        unknown_column("select pid as p from procs where p = 1"),
+        "p"
+    );
+    assert_eq!(
+        unknown_column("select p.pid as x from procs p where x = 1"),
+        "x"
+    );
+}
+
+#[test]
+fn alias_in_group_by_is_unknown() {
+    assert_eq!(
+        unknown_column("select uid as u, count(*) from procs group by u"),
+        "u"
+    );
+    assert_eq!(
+        query("select uid as u, count(*) from procs group by uid order by u"),
+        vec!["0,2", "1000,1"]
+    );
+}
+
+#[test]
+fn alias_in_order_by_and_having() {
+    assert_eq!(
+        query("select pid as p from procs order by p desc"),
+        vec!["42", "7", "1"]
+    );
+    assert_eq!(
+        query("select uid, count(*) as n from procs group by uid having n > 1"),
+        vec!["0,2"]
+    );
+    assert_eq!(
+        query("select p.pid as x from procs p order by x limit 1"),
+        vec!["1"]
+    );
+}
+
+#[test]
+fn result_columns_are_typed() {
+    use rusty_osquery::tables::ColumnType;
+    let result = engine()
+        .query("select p.pid, p.name as n, count(*), max(p.start_time), 'x' from procs p group by p.pid")
+        .unwrap();
+    let columns: Vec<(&str, Option<ColumnType>, Option<&str>)> = result
+        .columns
+        .iter()
+        .map(|c| (c.name.as_str(), c.kind, c.table.as_deref()))
+        .collect();
+    assert_eq!(
+        columns,
+        vec![
+            ("p.pid", Some(ColumnType::Integer), Some("procs")),
+            ("n", Some(ColumnType::Text), Some("procs")),
+            ("count(*)", Some(ColumnType::Integer), None),
+            ("max(p.start_time)", Some(ColumnType::Timestamp), None),
+            ("\"x\"", Some(ColumnType::Text), None),
+        ]
+    );
+    let result = engine().query("select * from process_args").unwrap();
+    assert_eq!(result.names(), vec!["pid", "index", "argument"]);
+    assert_eq!(result.columns[1].kind, Some(ColumnType::Integer));
+}
+
+#[test]
+fn huge_limit_and_offset() {
+    assert_eq!(
+        query("select pid from procs order by pid limit 18446744073709551615 offset 1"),
+        vec!["7", "42"]
+    );
+    assert_eq!(
+        query("select pid from procs limit 18446744073709551615 offset 1").len(),
+        2
+    );
+    assert!(query("select pid from procs offset 18446744073709551615").is_empty());
+}
+
+#[test]
+fn unknown_qualified_column_is_pointed_at() {
+    let sql = "select p.nosuch from procs p";
+    let e = error(sql);
+    assert_eq!(e.to_string(), "Unknown column p.nosuch in table procs");
+    assert_eq!(e.span(sql), Some(7..15));
+}
+
+#[test]
+fn sum_and_avg_only_count_numbers() {
+    assert_eq!(query("select sum(name) from procs"), vec!["0"]);
+    assert_eq!(
+        query("select sum(rss), avg(rss) from procs"),
+        vec!["1228800,614400"]
+    );
+    // Only "100" of pid 42's arguments is a number.
+    assert_eq!(
+        query("select avg(argument) from process_args where pid = 42"),
+        vec!["100"]
+    );
+    assert_eq!(query("select avg(name) from procs"), vec!["NULL"]);
+}
+
+#[test]
+fn sum_falls_back_to_real_on_overflow() {
+    assert_eq!(
+        query("select sum(9223372036854775807) from procs"),
+        vec!["27670116110564327000"]
+    );
+}
+
+#[test]
+fn bare_values_as_conditions() {
+    assert_eq!(query("select pid from procs where on_disk"), vec!["1"]);
+    assert_eq!(query("select pid from procs where not on_disk"), vec!["42"]);
+    assert_eq!(
+        query("select name from procs group by name having count(*) order by name"),
+        vec!["init", "kworker/0:1", "my (sleep)"]
+    );
+}
+
+#[test]
+fn inner_and_left_joins() {
+    let sql = "select p.pid, a.argument from procs p {} join process_args a \
+               on a.pid = p.pid and a.index = 1 order by p.pid";
+    assert_eq!(query(&sql.replace("{}", "inner")), vec!["42,100"]);
+    // Left joins keep every process, with NULLs where there's no argument.
+    assert_eq!(
+        query(&sql.replace("{}", "left")),
+        vec!["1,NULL", "7,NULL", "42,100"]
+    );
+}
+
+#[test]
+fn join_binds_required_columns_per_row() {
+    // proc_maps can't be scanned without a pid, so it's read once for each
+    // process with the pid from the join condition.
+    assert_eq!(
+        query(
+            "select p.pid, m.file_name from procs p join proc_maps m on m.pid = p.pid \
+             order by p.pid, m.start_addr"
+        ),
+        vec!["1,/sbin/init", "1,[stack]", "42,/usr/bin/sleep", "42,n/a"]
+    );
+    assert_eq!(
+        query(
+            "select p.name, m.inode from procs p left join proc_maps m on m.pid = p.pid \
+             where p.pid = 7"
+        ),
+        vec!["kworker/0:1,NULL"]
+    );
+}
+
+#[test]
+fn join_without_required_binding() {
+    let e = error("select m.pid from procs p join proc_maps m on m.inode = p.pid").to_string();
+    assert_eq!(
+        e,
+        "Table proc_maps needs m.pid = <value> in its join condition"
+    );
+}
+
+#[test]
+fn ambiguous_columns_in_joins() {
+    let e = error("select pid from procs p join process_args a on a.pid = p.pid").to_string();
+    assert_eq!(e, "Ambiguous column name pid");
+    // A column only one table has doesn't need qualifying.
+    assert_eq!(
+        query("select argument from procs p join process_args a on a.pid = p.pid where ppid = 0"),
+        vec!["/sbin/init"]
+    );
+}
+
+#[test]
+fn where_isnt_pushed_into_the_right_of_a_left_join() {
+    // Filtering process_args before joining would leave every process
+    // without arguments, rather than just the kernel thread.
+    assert_eq!(
+        query(
+            "select p.pid, a.argument from procs p left join process_args a on a.pid = p.pid \
+             where a.argument is null"
+        ),
+        vec!["7,NULL"]
+    );
+    assert_eq!(
+        query(
+            "select p.pid, a.argument from procs p left join process_args a on a.pid = p.pid \
+             where a.index = 0 order by p.pid"
+        ),
+        vec!["1,/sbin/init", "42,sleep"]
+    );
+}
@@ -0,0 +1,164 @@
+//! Runs the tables against the procfs captured in `tests/fixtures/root`:
+//! init (pid 1), a kernel thread (pid 7) and a user's `sleep` whose program
+//! has since been deleted (pid 42), with a few sockets between them. One of
+//! pid 7's fds is broken, to check it comes back as a warning. `fs` lists
+//! `/home/user` from the same tree.
+
+mod common;
+
+use common::{engine, query};
+
+#[test]
+fn procs_from_stat_and_status() {
+    let rows = query(
+        "select pid, ppid, name, cmdline, state, uid, euid, suid, gid, egid, sgid, threads, \
+         nice, priority, rss, vsize, start_time, user_time, system_time from procs order by pid",
+    );
+    assert_eq!(
+        rows,
+        vec![
+            "1,0,init,/sbin/init,S,0,0,0,0,0,0,1,0,20,409600,4096000,2023-11-14 22:13:20,100,200",
+            "7,2,kworker/0:1,kworker/0:1,I,0,0,0,0,0,0,1,0,20,NULL,0,2023-11-14 22:13:20,0,30",
+            "42,1,my (sleep),sleep 100 --name=a b,S,1000,1001,1002,100,101,102,2,5,20,819200,8192000,2023-11-14 22:14:10,2500,1200",
+        ]
+    );
+}
+
+#[test]
+fn procs_links() {
+    let rows = query("select pid, exe, cwd, root, on_disk from procs order by pid");
+    assert_eq!(
+        rows,
+        vec![
+            "1,/sbin/init,/,/,true",
+            "7,NULL,NULL,NULL,NULL",
+            "42,/usr/bin/sleep,/home/user,/,false",
+        ]
+    );
+}
+
+#[test]
+fn procs_pid_constraint() {
+    assert_eq!(
+        query("select pid, name from procs where pid in (42, 99)"),
+        vec!["42,my (sleep)"]
+    );
+    // Each process is read once, however often its pid is listed.
+    assert_eq!(
+        query("select pid, index from process_args where pid in (1, 1, 99, 1)"),
+        vec!["1,0"]
+    );
+}
+
+#[test]
+fn process_args() {
+    assert_eq!(
+        query("select pid, index, argument from process_args order by pid, index"),
+        vec![
+            "1,0,/sbin/init",
+            "42,0,sleep",
+            "42,1,100",
+            "42,2,--name=a b",
+        ]
+    );
+}
+
+#[test]
+fn process_open_files() {
+    assert_eq!(
+        query("select fd, path, type, flags, pos from process_open_files where pid = 42"),
+        vec![
+            "0,/dev/null,file,0100000,0",
+            "1,/tmp/out.log,file,02102001,120",
+            "3,socket:[555],socket,02,0",
+            "4,pipe:[77],pipe,NULL,NULL",
+            "5,socket:[777],socket,NULL,NULL",
+            "10,anon_inode:[eventfd],anon_inode,NULL,NULL",
+        ]
+    );
+}
+
+#[test]
+fn unreadable_fds_are_warned_about() {
+    // Pid 7's fd 9 is a plain file, so it can't be followed like a link.
+    let result = engine()
+        .query("select * from process_open_files where pid = 7")
+        .unwrap();
+    assert!(result.rows.is_empty());
+    let warnings: Vec<String> = result
+        .warnings
+        .iter()
+        .map(|w| w.path.display().to_string())
+        .collect();
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].ends_with("/proc/7/fd/9"), "{:?}", warnings);
+}
+
+#[test]
+fn process_open_sockets() {
+    // Pid 1's socket is a unix one, which isn't in /proc/net/tcp or udp.
+    assert_eq!(
+        query("select * from process_open_sockets where pid in (1, 42) order by pid, fd"),
+        vec![
+            "42,3,555,ipv6,tcp,::1,631,::,0,LISTEN,/usr/bin/sleep",
+            "42,5,777,ipv4,tcp,10.0.0.2,50000,8.8.8.8,443,ESTABLISHED,/usr/bin/sleep",
+        ]
+    );
+}
+
+#[test]
+fn net_decodes_addresses_and_states() {
+    assert_eq!(
+        query("select * from net order by inode"),
+        vec![
+            "tcp,ipv6,::1,631,::,0,LISTEN,0,0,1000,0,555,1",
+            "tcp,ipv4,127.0.0.1,53,0.0.0.0,0,LISTEN,0,0,0,0,666,1",
+            "tcp,ipv4,10.0.0.2,50000,8.8.8.8,443,ESTABLISHED,16,32,1000,0,777,2",
+        ]
+    );
+}
+
+#[test]
+fn net_keeps_raw_values() {
+    assert_eq!(
+        query("select raw_local_address, raw_remote_address, st from net where inode = 777"),
+        vec!["0200000A:C350,08080808:01BB,01"]
+    );
+}
+
+#[test]
+fn proc_maps() {
+    assert_eq!(
+        query("select * from proc_maps where pid in (1, 7, 42)"),
+        vec![
+            "1,55d0c0a00000,55d0c0a20000,r--p,00000000,08:01,1311,/sbin/init",
+            "1,7ffd5a1e0000,7ffd5a201000,rw-p,00000000,00:00,0,[stack]",
+            "42,5600aa000000,5600aa004000,r-xp,00002000,08:01,2222,/usr/bin/sleep",
+            "42,7f1200000000,7f1200021000,rw-p,00000000,00:00,0,n/a",
+        ]
+    );
+    assert_eq!(
+        query("select size_maybe, file_desc from proc_maps where pid = 42"),
+        vec!["00002000,2222", "00000000,0"]
+    );
+}
+
+#[test]
+fn os_version() {
+    assert_eq!(
+        query("select * from os_version"),
+        vec!["6.1.0-13-amd64,debian-kernel@lists.debian.org,gcc-12 (Debian 12.2.0-14) 12.2.0"]
+    );
+}
+
+#[test]
+fn fs_lists_under_the_root() {
+    // uid and created depend on the checkout, so they're left out.
+    assert_eq!(
+        query("select path, name, dir from fs where path = '/home/user' order by name"),
+        vec![
+            "/home/user,/home/user/notes.txt,false",
+            "/home/user,/home/user/projects,true",
+        ]
+    );
+}